@@ -0,0 +1,85 @@
+//! Compares cropping pieces with the scanline rasterizer against the former inside test of every
+//! pixel against the Bézier contour.
+//!
+//! Run with `cargo bench -p jigsaw_puzzle_generator --bench crop`.
+
+use glam::DVec2;
+use jigsaw_puzzle_generator::image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use jigsaw_puzzle_generator::{CropOptions, GameMode, JigsawGenerator, JigsawPiece};
+use rayon::iter::ParallelIterator;
+use std::time::{Duration, Instant};
+
+/// The number of pieces which are cropped by every method
+const PIECES: usize = 25;
+
+/// Crops a piece by testing every pixel against the Bézier contour
+fn crop_per_pixel(piece: &JigsawPiece, image: &DynamicImage) -> RgbaImage {
+    let mut piece_image = image
+        .view(
+            piece.top_left_x,
+            piece.top_left_y,
+            piece.crop_width,
+            piece.crop_height,
+        )
+        .to_image();
+    piece_image
+        .par_enumerate_pixels_mut()
+        .for_each(|(x, y, pixel)| {
+            let point = DVec2::new(
+                piece.top_left_x as f64 + x as f64,
+                piece.top_left_y as f64 + y as f64,
+            );
+            if !(piece.subpath.point_inside(point) || piece.subpath.contains_point(point)) {
+                *pixel = Rgba([0, 0, 0, 0])
+            }
+        });
+    piece_image
+}
+
+fn measure(name: &str, mut crop: impl FnMut()) -> Duration {
+    // warm up the thread pool and the caches
+    crop();
+    let start = Instant::now();
+    crop();
+    let elapsed = start.elapsed();
+    println!(
+        "{name:<24} {:>10.2?} total {:>10.2?} per piece",
+        elapsed,
+        elapsed / PIECES as u32
+    );
+    elapsed
+}
+
+fn main() {
+    let image =
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(1920, 1080, Rgba([120, 80, 40, 255])));
+    // the 500 pieces option of the game
+    let template = JigsawGenerator::new(image, 25, 20)
+        .seed(1)
+        .generate(GameMode::Classic, false)
+        .expect("Failed to generate puzzle");
+    let pieces = &template.pieces[..PIECES];
+    let image = &template.origin_image;
+
+    let per_pixel = measure("per-pixel inside test", || {
+        for piece in pieces {
+            crop_per_pixel(piece, image);
+        }
+    });
+    let scanline = measure("scanline", || {
+        for piece in pieces {
+            piece.crop(image);
+        }
+    });
+    measure("scanline 4x4 samples", || {
+        for piece in pieces {
+            piece
+                .crop_with_options(image, &CropOptions::default())
+                .unwrap();
+        }
+    });
+    println!(
+        "speedup: {:.1}x",
+        per_pixel.as_secs_f64() / scanline.as_secs_f64()
+    );
+}
@@ -0,0 +1,244 @@
+//! Packing of the piece images into texture atlases
+//!
+//! The crop rectangles of the pieces are packed into pages of a maximum size with a shelf
+//! algorithm: the pieces are sorted by height and placed side by side in rows, and a new row or
+//! page is started when one is full. The layout only depends on the sizes of the crop rectangles,
+//! so it is known before a single piece is cropped and the pages can be rendered one by one.
+
+use crate::{BevelOptions, CropOptions, JigsawResult, JigsawTemplate};
+use image::imageops::replace;
+use image::RgbaImage;
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+/// Options for [`JigsawTemplate::atlas_layout`] and [`JigsawTemplate::render_atlas_page`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasOptions {
+    /// Maximum width and height of a page in pixel. Pages grow beyond it only for pieces which
+    /// don't fit otherwise. Defaults to `4096`
+    pub max_size: u32,
+    /// Transparent pixels between the pieces and around the border of a page, so filtered
+    /// samples don't bleed into the neighbouring pieces. Defaults to `2`
+    pub padding: u32,
+    /// How the pieces are cropped. The pieces take the size of
+    /// [`crate::JigsawPiece::crop_size`] for the [`CropOptions::scale`]
+    pub crop: CropOptions,
+    /// How the color images of the pieces are shaded, `None` leaves them flat. The options are
+    /// scaled along with the pieces. Defaults to `None`
+    pub bevel: Option<BevelOptions>,
+}
+
+impl Default for AtlasOptions {
+    fn default() -> Self {
+        AtlasOptions {
+            max_size: 4096,
+            padding: 2,
+            crop: CropOptions::default(),
+            bevel: None,
+        }
+    }
+}
+
+impl AtlasOptions {
+    pub fn max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn crop(mut self, crop: CropOptions) -> Self {
+        self.crop = crop;
+        self
+    }
+
+    pub fn bevel(mut self, bevel: Option<BevelOptions>) -> Self {
+        self.bevel = bevel;
+        self
+    }
+}
+
+/// The place of a piece image in an atlas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasRect {
+    /// Index of the page
+    pub page: usize,
+    /// Left side of the image on the page in pixel
+    pub x: u32,
+    /// Top side of the image on the page in pixel
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Where the images of the pieces are placed in the pages of an atlas
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasLayout {
+    /// Width and height of every page
+    pub page_sizes: Vec<(u32, u32)>,
+    /// The place of every piece, by piece index
+    pub rects: Vec<AtlasRect>,
+}
+
+impl AtlasLayout {
+    /// Returns the indices of the pieces on the given page
+    pub fn pieces_on_page(&self, page: usize) -> impl Iterator<Item = usize> + '_ {
+        self.rects
+            .iter()
+            .enumerate()
+            .filter(move |(_, rect)| rect.page == page)
+            .map(|(index, _)| index)
+    }
+
+    /// Returns the texture coordinates of the piece as `[min_u, min_v, max_u, max_v]`, between
+    /// `0.0` and `1.0` with `v` pointing down
+    pub fn uv_rect(&self, index: usize) -> [f32; 4] {
+        let rect = self.rects[index];
+        let (width, height) = self.page_sizes[rect.page];
+        let (width, height) = (width as f32, height as f32);
+        [
+            rect.x as f32 / width,
+            rect.y as f32 / height,
+            (rect.x + rect.width) as f32 / width,
+            (rect.y + rect.height) as f32 / height,
+        ]
+    }
+}
+
+/// A rendered page of an atlas
+#[derive(Debug, Clone)]
+pub struct AtlasPage {
+    /// The cropped pieces
+    pub color: RgbaImage,
+    /// The white silhouettes of the pieces, at the same places as in [`AtlasPage::color`]
+    pub white: RgbaImage,
+}
+
+impl JigsawTemplate {
+    /// Packs the crop rectangles of all pieces into atlas pages. This is cheap, the pieces are
+    /// cropped by [`JigsawTemplate::render_atlas_page`].
+    ///
+    /// Returns an error if the scale of the crop options is not a positive number.
+    pub fn atlas_layout(&self, options: &AtlasOptions) -> JigsawResult<AtlasLayout> {
+        options.crop.validate()?;
+        let padding = options.padding;
+        let mut order: Vec<usize> = (0..self.pieces.len()).collect();
+        order.sort_by_key(|&index| {
+            std::cmp::Reverse(self.pieces[index].crop_size(options.crop.scale).1)
+        });
+
+        let mut page_sizes = vec![];
+        let mut rects = vec![None; self.pieces.len()];
+        // the page size, the cursor, the top of the current row and the height of the row
+        let (mut size, mut x, mut y, mut row_height) = (0, 0, 0, 0);
+        for index in order {
+            let (width, height) = self.pieces[index].crop_size(options.crop.scale);
+            if x + width + padding > size {
+                // start a new row
+                x = padding;
+                y += row_height + padding;
+                row_height = 0;
+            }
+            if page_sizes.is_empty() || y + height + padding > size || x + width + padding > size {
+                // start a new page, which is at least large enough for this piece
+                size = options.max_size.max(width.max(height) + 2 * padding);
+                page_sizes.push((0, 0));
+                (x, y, row_height) = (padding, padding, 0);
+            }
+            let page = page_sizes.len() - 1;
+            rects[index] = Some(AtlasRect {
+                page,
+                x,
+                y,
+                width,
+                height,
+            });
+            // the pages are cropped to the used area
+            let used = &mut page_sizes[page];
+            *used = (
+                used.0.max(x + width + padding),
+                used.1.max(y + height + padding),
+            );
+            x += width + padding;
+            row_height = row_height.max(height);
+        }
+
+        Ok(AtlasLayout {
+            page_sizes,
+            rects: rects.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Crops the pieces on the given page of the layout and draws them into the page
+    ///
+    /// Returns an error if the scale of the crop options is not a positive number.
+    pub fn render_atlas_page(
+        &self,
+        layout: &AtlasLayout,
+        page: usize,
+        options: &AtlasOptions,
+    ) -> JigsawResult<AtlasPage> {
+        options.crop.validate()?;
+        let (width, height) = layout.page_sizes[page];
+        let indices: Vec<usize> = layout.pieces_on_page(page).collect();
+        #[cfg(feature = "parallel")]
+        let indices = indices.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let indices = indices.into_iter();
+        let images: Vec<_> = indices
+            .map(|index| {
+                let piece = &self.pieces[index];
+                let cropped = piece.render_crop(&self.origin_image, &options.crop);
+                let white = piece.fill_white(&cropped);
+                let color = match &options.bevel {
+                    Some(bevel) => bevel.scaled(options.crop.scale).apply(&cropped),
+                    None => cropped,
+                };
+                (index, color.to_rgba8(), white.to_rgba8())
+            })
+            .collect();
+
+        let mut atlas_page = AtlasPage {
+            color: RgbaImage::new(width, height),
+            white: RgbaImage::new(width, height),
+        };
+        for (index, color, white) in images {
+            let rect = layout.rects[index];
+            replace(&mut atlas_page.color, &color, rect.x as i64, rect.y as i64);
+            replace(&mut atlas_page.white, &white, rect.x as i64, rect.y as i64);
+        }
+        Ok(atlas_page)
+    }
+
+    /// Packs the images of all pieces into atlas pages, see [`JigsawTemplate::atlas_layout`]
+    pub fn pack_atlas(
+        &self,
+        options: &AtlasOptions,
+    ) -> JigsawResult<(AtlasLayout, Vec<AtlasPage>)> {
+        let layout = self.atlas_layout(options)?;
+        let pages = (0..layout.page_sizes.len())
+            .map(|page| self.render_atlas_page(&layout, page, options))
+            .collect::<JigsawResult<_>>()?;
+        Ok((layout, pages))
+    }
+
+    /// Packs the images of all pieces into atlas pages once for every one of the scale factors,
+    /// for levels of detail which replace each other depending on the zoom. The layouts of the
+    /// levels are independent, a piece may be on different pages in each of them.
+    pub fn pack_atlas_lods(
+        &self,
+        options: &AtlasOptions,
+        scales: &[f32],
+    ) -> JigsawResult<Vec<(AtlasLayout, Vec<AtlasPage>)>> {
+        scales
+            .iter()
+            .map(|&scale| {
+                let options = options.clone().crop(options.crop.clone().scale(scale));
+                self.pack_atlas(&options)
+            })
+            .collect()
+    }
+}
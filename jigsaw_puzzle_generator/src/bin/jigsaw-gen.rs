@@ -0,0 +1,278 @@
+//! Generates a jigsaw puzzle from an image and writes the cropped pieces, the cut lines and a
+//! manifest of the pieces into an output directory.
+//!
+//! ```text
+//! jigsaw-gen photo.jpg --pieces 500 --mode classic --seed 7 --output puzzles/photo
+//! ```
+
+use clap::{ArgGroup, Parser, ValueEnum};
+use env_logger::{Builder, Env};
+use jigsaw_puzzle_generator::{
+    CropOptions, GameMode, JigsawGenerator, JigsawResult, JigsawTemplate, SvgOptions,
+};
+use log::info;
+use serde::Serialize;
+use std::fs::{create_dir_all, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Generates jigsaw puzzles from images")]
+#[command(group(ArgGroup::new("count").required(true).args(["columns", "pieces"])))]
+struct Args {
+    /// Path of the source image
+    image: PathBuf,
+    /// Number of columns of pieces
+    #[arg(long, requires = "rows")]
+    columns: Option<usize>,
+    /// Number of rows of pieces
+    #[arg(long, requires = "columns")]
+    rows: Option<usize>,
+    /// Total number of pieces, split into the columns and rows with the most square pieces
+    #[arg(long)]
+    pieces: Option<usize>,
+    /// Shape of the pieces
+    #[arg(long, value_enum, default_value_t = Mode::Classic)]
+    mode: Mode,
+    /// Seed of the contours, a random one is picked and written to the manifest by default
+    #[arg(long)]
+    seed: Option<usize>,
+    /// Size of the tabs between 10 and 30
+    #[arg(long)]
+    tab_size: Option<f32>,
+    /// Asymmetry of the pieces between 0 and 13
+    #[arg(long)]
+    jitter: Option<f32>,
+    /// Treat pixels with an alpha value up to this one as transparent background and drop the
+    /// pieces without any other pixel
+    #[arg(long)]
+    alpha_threshold: Option<u8>,
+    /// Let the right border join the left one and the bottom border the top one, in the classic
+    /// and square modes
+    #[arg(long)]
+    wrap_around: bool,
+    /// Scale the image down to at most 1920x1200 pixels
+    #[arg(long)]
+    resize: bool,
+    /// Directory the files are written to
+    #[arg(short, long, default_value = "output")]
+    output: PathBuf,
+    /// Files to write
+    #[arg(
+        short,
+        long = "format",
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [Format::Png, Format::Svg, Format::Json]
+    )]
+    formats: Vec<Format>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Mode {
+    Classic,
+    Square,
+    Hexagon,
+    Irregular,
+}
+
+impl From<Mode> for GameMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Classic => GameMode::Classic,
+            Mode::Square => GameMode::Square,
+            Mode::Hexagon => GameMode::Hexagon,
+            Mode::Irregular => GameMode::Irregular,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    /// A transparent PNG image of every piece
+    Png,
+    /// The cut lines of the whole puzzle as `puzzle.svg`
+    Svg,
+    /// The position of every piece as `manifest.json`
+    Json,
+}
+
+/// The content of `manifest.json`
+#[derive(Debug, Serialize)]
+struct Manifest {
+    image: PathBuf,
+    width: u32,
+    height: u32,
+    columns: usize,
+    rows: usize,
+    mode: GameMode,
+    seed: usize,
+    pieces: Vec<PieceEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct PieceEntry {
+    index: usize,
+    /// The PNG image of the piece, relative to the manifest
+    file: String,
+    /// The top left corner of the piece image in the puzzle
+    top_left: (u32, u32),
+    /// The size of the piece image
+    size: (u32, u32),
+    /// The offset of the starting point of the piece from the top left corner of its image
+    offset: (f32, f32),
+    start_point: (f32, f32),
+    is_border: bool,
+}
+
+fn main() -> ExitCode {
+    Builder::from_env(Env::default().default_filter_or("info"))
+        .format_timestamp_millis()
+        .init();
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let image = image::open(&args.image)?;
+    let generator = match (args.columns, args.rows, args.pieces) {
+        (Some(columns), Some(rows), _) => JigsawGenerator::new(image, columns, rows),
+        (_, _, Some(pieces)) => JigsawGenerator::with_target_pieces(image, pieces)?,
+        _ => unreachable!("clap requires either the columns and rows or the pieces"),
+    };
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut generator = generator.seed(seed);
+    if let Some(tab_size) = args.tab_size {
+        generator = generator.tab_size(tab_size);
+    }
+    if let Some(jitter) = args.jitter {
+        generator = generator.jitter(jitter);
+    }
+    if let Some(threshold) = args.alpha_threshold {
+        generator = generator.alpha_threshold(threshold);
+    }
+    generator = generator.wrap_around(args.wrap_around);
+    let game_mode = GameMode::from(args.mode);
+    let template = generator.generate(game_mode, args.resize)?;
+    info!(
+        "generated {} pieces from {}",
+        template.pieces.len(),
+        args.image.display()
+    );
+
+    create_dir_all(&args.output)?;
+    if args.formats.contains(&Format::Png) {
+        write_pieces(&template, &args.output)?;
+    }
+    if args.formats.contains(&Format::Svg) {
+        let svg = template.to_svg(&SvgOptions::default())?;
+        std::fs::write(args.output.join("puzzle.svg"), svg)?;
+    }
+    if args.formats.contains(&Format::Json) {
+        let (width, height) = template.image_dimensions();
+        let manifest = Manifest {
+            image: args.image.clone(),
+            width,
+            height,
+            columns: generator.pieces_in_column(),
+            rows: generator.pieces_in_row(),
+            mode: game_mode,
+            seed,
+            pieces: template
+                .pieces
+                .iter()
+                .map(|piece| PieceEntry {
+                    index: piece.index,
+                    file: piece_file_name(piece.index),
+                    top_left: (piece.top_left_x, piece.top_left_y),
+                    size: (piece.crop_width, piece.crop_height),
+                    offset: piece.calc_offset(),
+                    start_point: piece.start_point,
+                    is_border: piece.is_boarder,
+                })
+                .collect(),
+        };
+        let file = BufWriter::new(File::create(args.output.join("manifest.json"))?);
+        serde_json::to_writer_pretty(file, &manifest)?;
+    }
+    info!("written to {}", args.output.display());
+    Ok(())
+}
+
+fn write_pieces(template: &JigsawTemplate, output: &Path) -> JigsawResult<()> {
+    let options = CropOptions::default();
+    for piece in template.pieces.iter() {
+        piece
+            .crop_with_options(&template.origin_image, &options)?
+            .save(output.join(piece_file_name(piece.index)))?;
+    }
+    Ok(())
+}
+
+fn piece_file_name(index: usize) -> String {
+    format!("piece_{index}.png")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+    use serde_json::Value;
+    use std::fs::{read_to_string, remove_dir_all};
+
+    #[test]
+    fn test_run() {
+        let dir = std::env::temp_dir().join(format!("jigsaw-gen-test-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        let image_path = dir.join("image.png");
+        RgbaImage::from_fn(120, 90, |x, y| Rgba([x as u8, y as u8, 100, 255]))
+            .save(&image_path)
+            .unwrap();
+        let output = dir.join("output");
+        let args = Args::parse_from([
+            "jigsaw-gen",
+            image_path.to_str().unwrap(),
+            "--columns",
+            "3",
+            "--rows",
+            "2",
+            "--seed",
+            "5",
+            "--output",
+            output.to_str().unwrap(),
+        ]);
+        run(&args).unwrap();
+
+        let template = JigsawGenerator::new(image::open(&image_path).unwrap(), 3, 2)
+            .seed(5)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        assert!(read_to_string(output.join("puzzle.svg"))
+            .unwrap()
+            .contains("<svg"));
+        let manifest: Value =
+            serde_json::from_str(&read_to_string(output.join("manifest.json")).unwrap()).unwrap();
+        assert_eq!(manifest["width"], 120);
+        assert_eq!(manifest["height"], 90);
+        assert_eq!(manifest["seed"], 5);
+        let pieces = manifest["pieces"].as_array().unwrap();
+        assert_eq!(pieces.len(), 6);
+        for (entry, piece) in pieces.iter().zip(template.pieces.iter()) {
+            let (x, y) = piece.calc_offset();
+            assert_eq!(entry["offset"][0].as_f64().unwrap(), x as f64);
+            assert_eq!(entry["offset"][1].as_f64().unwrap(), y as f64);
+            let png = image::open(output.join(entry["file"].as_str().unwrap())).unwrap();
+            assert_eq!(png.width() as u64, entry["size"][0].as_u64().unwrap());
+            assert_eq!(png.height() as u64, entry["size"][1].as_u64().unwrap());
+        }
+
+        remove_dir_all(&dir).unwrap();
+    }
+}
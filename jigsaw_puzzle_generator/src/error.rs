@@ -0,0 +1,167 @@
+//! The errors of the generator
+
+use crate::GameMode;
+use image::ImageError;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The result type of the generator
+pub type JigsawResult<T> = Result<T, JigsawError>;
+
+/// Everything that can go wrong while generating a jigsaw puzzle
+#[derive(Debug)]
+pub enum JigsawError {
+    /// The tab size is not between `10.0` and `30.0`
+    InvalidTabSize(f32),
+    /// The jitter is not between `0.0` and `13.0`
+    InvalidJitter(f32),
+    /// The warp is not between `0.0` and `25.0`
+    InvalidWarp(f32),
+    /// The aspect ratio to crop the image to is not a positive number
+    InvalidAspectRatio(f32),
+    /// The resolution of a physical export is not a positive number
+    InvalidDpi(f32),
+    /// The factor to scale a template or the image of a piece by is not a positive number
+    InvalidScale(f32),
+    /// The pieces of the given game mode can't wrap around the borders of the image
+    UnsupportedWrapAround(GameMode),
+    /// There are no pieces in a column or a row
+    ZeroPieces {
+        pieces_in_column: usize,
+        pieces_in_row: usize,
+    },
+    /// The pieces would be smaller than [`MIN_PIECE_SIZE`](crate::MIN_PIECE_SIZE) pixels
+    ImageTooSmall {
+        width: u32,
+        height: u32,
+        pieces_in_column: usize,
+        pieces_in_row: usize,
+    },
+    /// The raw buffer doesn't hold exactly four bytes for every pixel
+    InvalidRawBuffer { width: u32, height: u32, len: usize },
+    /// The contour of the piece with the given index has no bounding box
+    EmptyBoundingBox(usize),
+    /// The [`EdgeProfile`](crate::EdgeProfile) of the generator returned no segments for a tab
+    EmptyProfile,
+    /// The Voronoi cell of the piece with the given index has less than three corners
+    DegeneratedCell(usize),
+    /// An edge of the piece with the given index is not on the border of the image but doesn't
+    /// have exactly one neighbouring piece
+    UnsharedEdge { index: usize, owners: usize },
+    /// Pixels of the piece with the given index are outside of its crop rectangle
+    ClippedPiece { index: usize, x: u32, y: u32 },
+    /// Pixels are not covered by any piece, the first one is given
+    UncoveredPixels { count: usize, x: u32, y: u32 },
+    /// Pixels are covered by more than one piece, the first one is given
+    OverlappingPixels { count: usize, x: u32, y: u32 },
+    /// The image for a template doesn't have the aspect ratio of the template's image
+    MismatchedImage {
+        width: u32,
+        height: u32,
+        template_width: u32,
+        template_height: u32,
+    },
+    /// The image couldn't be loaded or encoded
+    Image(ImageError),
+    /// An SVG document couldn't be written
+    Format(std::fmt::Error),
+}
+
+impl Display for JigsawError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JigsawError::InvalidTabSize(tab_size) => {
+                write!(f, "tab size {tab_size} is not between 10 and 30")
+            }
+            JigsawError::InvalidJitter(jitter) => {
+                write!(f, "jitter {jitter} is not between 0 and 13")
+            }
+            JigsawError::InvalidWarp(warp) => write!(f, "warp {warp} is not between 0 and 25"),
+            JigsawError::InvalidAspectRatio(aspect_ratio) => {
+                write!(f, "aspect ratio {aspect_ratio} is not a positive number")
+            }
+            JigsawError::InvalidDpi(dpi) => write!(f, "DPI {dpi} is not a positive number"),
+            JigsawError::InvalidScale(scale) => {
+                write!(f, "scale {scale} is not a positive number")
+            }
+            JigsawError::UnsupportedWrapAround(game_mode) => {
+                write!(f, "{game_mode:?} pieces can't wrap around")
+            }
+            JigsawError::ZeroPieces {
+                pieces_in_column,
+                pieces_in_row,
+            } => write!(
+                f,
+                "{pieces_in_column}x{pieces_in_row} pieces, there has to be at least one piece in every column and row"
+            ),
+            JigsawError::ImageTooSmall {
+                width,
+                height,
+                pieces_in_column,
+                pieces_in_row,
+            } => write!(
+                f,
+                "image of {width}x{height} pixels is too small for {pieces_in_column}x{pieces_in_row} pieces"
+            ),
+            JigsawError::InvalidRawBuffer { width, height, len } => write!(
+                f,
+                "raw buffer of {len} bytes doesn't match an RGBA image of {width}x{height} pixels"
+            ),
+            JigsawError::EmptyBoundingBox(index) => {
+                write!(f, "no bounding box found for piece {index}")
+            }
+            JigsawError::EmptyProfile => write!(f, "the edge profile returned no segments"),
+            JigsawError::DegeneratedCell(index) => {
+                write!(f, "degenerated cell for piece {index}")
+            }
+            JigsawError::UnsharedEdge { index, owners } => {
+                write!(f, "an inner edge of piece {index} belongs to {owners} pieces")
+            }
+            JigsawError::ClippedPiece { index, x, y } => write!(
+                f,
+                "piece {index} is clipped by its crop rectangle at pixel ({x}, {y})"
+            ),
+            JigsawError::UncoveredPixels { count, x, y } => write!(
+                f,
+                "{count} pixels are not covered by any piece, the first at ({x}, {y})"
+            ),
+            JigsawError::OverlappingPixels { count, x, y } => write!(
+                f,
+                "{count} pixels are covered by several pieces, the first at ({x}, {y})"
+            ),
+            JigsawError::MismatchedImage {
+                width,
+                height,
+                template_width,
+                template_height,
+            } => write!(
+                f,
+                "image of {width}x{height} pixels doesn't match a template for {template_width}x{template_height} pixels"
+            ),
+            JigsawError::Image(error) => write!(f, "image error: {error}"),
+            JigsawError::Format(error) => write!(f, "format error: {error}"),
+        }
+    }
+}
+
+impl Error for JigsawError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JigsawError::Image(error) => Some(error),
+            JigsawError::Format(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ImageError> for JigsawError {
+    fn from(error: ImageError) -> Self {
+        JigsawError::Image(error)
+    }
+}
+
+impl From<std::fmt::Error> for JigsawError {
+    fn from(error: std::fmt::Error) -> Self {
+        JigsawError::Format(error)
+    }
+}
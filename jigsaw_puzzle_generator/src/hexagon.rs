@@ -0,0 +1,130 @@
+//! Generator for [`GameMode::Hexagon`](crate::GameMode::Hexagon)
+//!
+//! The image is divided into rows of pointy-top hexagons. Every odd row is shifted by half a
+//! piece, so it starts and ends with a half piece. Two neighbouring rows are separated by a
+//! zigzag line whose vertices lie `piece_height / 6` above and below the row boundary. The
+//! vertical sides of a row run between the zigzag lines, and the top and bottom border of the
+//! image are straight.
+
+use crate::{round, Edge, IndentedEdge, JigsawGenerator, JigsawPiece, JigsawResult, StraightEdge};
+use log::debug;
+
+impl JigsawGenerator {
+    // `usize::is_multiple_of` needs Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
+    pub(crate) fn hexagon_generator(
+        &self,
+        image_size: (u32, u32),
+        piece_width: f32,
+        piece_height: f32,
+    ) -> JigsawResult<Vec<JigsawPiece>> {
+        let image_width = image_size.0 as f32;
+        let image_height = image_size.1 as f32;
+        let columns = self.pieces_in_column;
+        let rows = self.pieces_in_row;
+        let mut contour_gen = self.contour_generator(piece_width, piece_height)?;
+
+        // Vertices of the zigzag lines and the vertical sides lie on multiples of half a piece
+        let half_steps = 2 * columns;
+        let xs: Vec<f32> = (0..=half_steps)
+            .map(|j| {
+                if j == half_steps {
+                    image_width
+                } else {
+                    round(j as f32 * piece_width / 2.0)
+                }
+            })
+            .collect();
+        let depth = piece_height / 6.0;
+        // y coordinate of the vertex `j` of the line between row `b - 1` and row `b`
+        let line_y = |b: usize, j: usize| {
+            if b == 0 {
+                0.0
+            } else if b == rows {
+                image_height
+            } else if (b + j) % 2 == 0 {
+                round(b as f32 * piece_height + depth)
+            } else {
+                round(b as f32 * piece_height - depth)
+            }
+        };
+
+        // zigzag_edges[b - 1][j] runs from vertex `j` to vertex `j + 1` of the inner line `b`
+        let zigzag_edges: Vec<Vec<Edge>> = (1..rows)
+            .map(|b| {
+                (0..half_steps)
+                    .map(|j| {
+                        IndentedEdge::new_along(
+                            (xs[j], line_y(b, j)),
+                            (xs[j + 1], line_y(b, j + 1)),
+                            &mut contour_gen,
+                        )
+                        .map(Edge::IndentedEdge)
+                    })
+                    .collect()
+            })
+            .collect::<JigsawResult<_>>()?;
+
+        let mut pieces = vec![];
+        for row in 0..rows {
+            // even rows have their sides on even vertices, odd rows on odd ones and the borders
+            let mut sides: Vec<usize> = (row % 2..=half_steps).step_by(2).collect();
+            if row % 2 == 1 {
+                sides.insert(0, 0);
+                sides.push(half_steps);
+            }
+            let side_edges: Vec<Edge> = sides
+                .iter()
+                .map(|&j| {
+                    let starting_point = (xs[j], line_y(row, j));
+                    let end_point = (xs[j], line_y(row + 1, j));
+                    if j == 0 || j == half_steps {
+                        Ok(Edge::StraightEdge(StraightEdge {
+                            starting_point,
+                            end_point,
+                        }))
+                    } else {
+                        IndentedEdge::new_along(starting_point, end_point, &mut contour_gen)
+                            .map(Edge::IndentedEdge)
+                    }
+                })
+                .collect::<JigsawResult<_>>()?;
+
+            for (cell, (&left, &right)) in sides.iter().zip(sides.iter().skip(1)).enumerate() {
+                let index = pieces.len();
+                debug!("starting process hexagon piece {index}");
+                let mut edges = vec![];
+                if row == 0 {
+                    edges.push(Edge::StraightEdge(StraightEdge {
+                        starting_point: (xs[left], 0.0),
+                        end_point: (xs[right], 0.0),
+                    }));
+                } else {
+                    edges.extend(zigzag_edges[row - 1][left..right].iter().cloned());
+                }
+                edges.push(side_edges[cell + 1].clone());
+                if row == rows - 1 {
+                    edges.push(Edge::StraightEdge(StraightEdge {
+                        starting_point: (xs[left], image_height),
+                        end_point: (xs[right], image_height),
+                    }));
+                } else {
+                    edges.extend(zigzag_edges[row][left..right].iter().rev().cloned());
+                }
+                edges.push(side_edges[cell].clone());
+
+                let is_boarder = row == 0 || row == rows - 1 || left == 0 || right == half_steps;
+                pieces.push(JigsawPiece::new(
+                    index,
+                    (xs[left], round(row as f32 * piece_height)),
+                    image_size,
+                    (xs[right] - xs[left], piece_height),
+                    edges,
+                    is_boarder,
+                )?);
+            }
+        }
+
+        Ok(pieces)
+    }
+}
@@ -0,0 +1,97 @@
+//! Rasterisation of all pieces of a template into a single map of piece indices
+//!
+//! Every edge is flattened once and the polyline is shared by both pieces beside it, so the
+//! crossings of a row with the two pieces are exactly the same numbers. Filling the half-open
+//! spans between them assigns every pixel on a shared edge to exactly one of the pieces.
+
+use crate::svg::edge_key;
+use crate::{raster, JigsawTemplate};
+use glam::DVec2;
+use image::{ImageBuffer, Luma};
+#[cfg(feature = "parallel")]
+use rayon::{iter::IndexedParallelIterator, iter::ParallelIterator, slice::ParallelSliceMut};
+use std::collections::HashMap;
+
+/// An image holding the index of the piece that owns every pixel, see
+/// [`JigsawTemplate::label_map`]
+pub type LabelMap = ImageBuffer<Luma<u32>, Vec<u32>>;
+
+/// Label of the pixels that aren't covered by any piece
+pub const NO_PIECE: u32 = u32::MAX;
+
+/// Label of the pixels that are covered by more than one piece
+pub const OVERLAPPING_PIECES: u32 = u32::MAX - 1;
+
+/// The flattened contour of a piece
+struct PieceSegments {
+    label: u32,
+    min_y: f64,
+    max_y: f64,
+    segments: Vec<(DVec2, DVec2)>,
+}
+
+impl JigsawTemplate {
+    /// Returns an image of the size of the template image which holds the index of the piece
+    /// that owns every pixel, tested at the pixel centre. Pixels outside of all pieces are
+    /// [`NO_PIECE`], pixels inside of several pieces are [`OVERLAPPING_PIECES`].
+    ///
+    /// All pieces are rasterised in one pass over the rows of the image, so this is much cheaper
+    /// than testing every piece on its own.
+    pub fn label_map(&self) -> LabelMap {
+        let (width, height) = self.image_dimensions();
+        let mut polylines = HashMap::new();
+        let pieces: Vec<PieceSegments> = self
+            .pieces
+            .iter()
+            .map(|piece| {
+                let mut segments = vec![];
+                for edge in piece.edges.iter() {
+                    let polyline = polylines
+                        .entry(edge_key(edge))
+                        .or_insert_with(|| raster::flatten_open(&edge.to_beziers(false)));
+                    segments.extend(polyline.windows(2).map(|points| (points[0], points[1])));
+                }
+                let (min_y, max_y) = segments
+                    .iter()
+                    .flat_map(|(a, b)| [a.y, b.y])
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), y| {
+                        (min.min(y), max.max(y))
+                    });
+                PieceSegments {
+                    label: piece.index as u32,
+                    min_y,
+                    max_y,
+                    segments,
+                }
+            })
+            .collect();
+
+        let mut labels = vec![NO_PIECE; (width * height) as usize];
+        if width > 0 {
+            #[cfg(feature = "parallel")]
+            let rows = labels.par_chunks_mut(width as usize);
+            #[cfg(not(feature = "parallel"))]
+            let rows = labels.chunks_mut(width as usize);
+            rows.enumerate().for_each(|(row, labels)| {
+                let y = row as f64 + 0.5;
+                let mut crossings = vec![];
+                for piece in pieces.iter().filter(|p| y >= p.min_y && y < p.max_y) {
+                    raster::find_crossings(&piece.segments, y, &mut crossings);
+                    for span in crossings.chunks_exact(2) {
+                        // the pixel centres at `x + 0.5` for x in first..last
+                        let first = (span[0] - 0.5).ceil().max(0.0) as usize;
+                        let last = ((span[1] - 0.5).ceil().max(0.0) as usize).min(labels.len());
+                        for label in labels[first.min(last)..last].iter_mut() {
+                            *label = if *label == NO_PIECE {
+                                piece.label
+                            } else {
+                                OVERLAPPING_PIECES
+                            };
+                        }
+                    }
+                }
+            });
+        }
+        LabelMap::from_raw(width, height, labels).expect("one label for every pixel")
+    }
+}
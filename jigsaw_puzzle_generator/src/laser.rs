@@ -0,0 +1,337 @@
+//! Export of templates for laser cutters and plotters
+//!
+//! The cut lines are written in millimetres, converted from pixels with the resolution the image
+//! is printed at. Every edge between two pieces is a single open stroke which is emitted exactly
+//! once, so the cutter never passes the same line twice. The outer border of the puzzle is
+//! written as closed loops on a separate layer.
+//!
+//! A laser removes a strip of material as wide as its kerf. **Only the outer border is
+//! compensated for it**: the border is offset by half the kerf, so the finished puzzle keeps its
+//! nominal size. The inner edges are cut along their nominal line, so every piece loses half the
+//! kerf along each of its inner edges. Neighbouring pieces lose the same amount and still fit each
+//! other, but with a gap as wide as the kerf.
+
+use crate::svg::{edge_key, write_bezier, write_move_to};
+use crate::{raster, round, JigsawError, JigsawResult, JigsawTemplate};
+use bezier_rs::Bezier;
+use glam::DVec2;
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+
+/// Millimetres per inch
+const MM_PER_INCH: f64 = 25.4;
+
+/// Maximum distance in pixels between the end points of two connected border edges
+const JOIN_TOLERANCE: f64 = 0.05;
+
+/// Lower bound of the cosine of half the angle between two offset border segments, which limits
+/// the miter of sharp corners to four times the offset
+const MIN_MITER_COS: f64 = 0.25;
+
+/// Options for [`JigsawTemplate::to_laser_svg`] and [`JigsawTemplate::to_dxf`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaserOptions {
+    /// Resolution the image is printed at in pixels per inch. Defaults to `96.0`
+    pub dpi: f32,
+    /// Width of the cut in millimetres. Only the outer border is offset, it grows by half of it
+    /// and negative values shrink it. The inner edges are cut along their nominal line, so the
+    /// pieces fit each other with a gap as wide as the kerf. Defaults to `0.0`
+    pub kerf: f32,
+    /// Width of the cut lines in the SVG document in millimetres. Defaults to `0.1`
+    pub stroke_width: f32,
+}
+
+impl Default for LaserOptions {
+    fn default() -> Self {
+        LaserOptions {
+            dpi: 96.0,
+            kerf: 0.0,
+            stroke_width: 0.1,
+        }
+    }
+}
+
+impl LaserOptions {
+    pub fn dpi(mut self, dpi: f32) -> Self {
+        self.dpi = dpi;
+        self
+    }
+
+    pub fn kerf(mut self, kerf: f32) -> Self {
+        self.kerf = kerf;
+        self
+    }
+
+    pub fn stroke_width(mut self, stroke_width: f32) -> Self {
+        self.stroke_width = stroke_width;
+        self
+    }
+}
+
+/// The cut lines of a template in millimetres
+struct LaserPaths {
+    width: f64,
+    height: f64,
+    /// The edges between two pieces
+    cuts: Vec<Vec<Bezier>>,
+    /// The closed loops of the outer border, already offset by half the kerf
+    border: Vec<Vec<DVec2>>,
+}
+
+impl JigsawTemplate {
+    /// Serialises the template into an SVG document for laser cutters, measured in millimetres.
+    /// The inner edges are in the `cut` layer, the outer border in the `border` layer.
+    ///
+    /// Returns an error if the DPI are not a positive number.
+    pub fn to_laser_svg(&self, options: &LaserOptions) -> JigsawResult<String> {
+        let paths = self.laser_paths(options)?;
+        let margin = (options.kerf.max(0.0) as f64 / 2.0 + options.stroke_width as f64) as f32;
+        let (width, height) = (paths.width as f32, paths.height as f32);
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" version="1.1" width="{}mm" height="{}mm" viewBox="{} {} {} {}">"#,
+            round(width + 2.0 * margin),
+            round(height + 2.0 * margin),
+            round(-margin),
+            round(-margin),
+            round(width + 2.0 * margin),
+            round(height + 2.0 * margin),
+        )?;
+        writeln!(
+            svg,
+            r##"  <g id="cut" inkscape:groupmode="layer" inkscape:label="cut" fill="none" stroke="#000000" stroke-width="{}">"##,
+            options.stroke_width
+        )?;
+        for beziers in paths.cuts.iter() {
+            let mut path = String::new();
+            write_move_to(&mut path, beziers[0].start);
+            for bezier in beziers.iter() {
+                write_bezier(&mut path, bezier);
+            }
+            writeln!(svg, r#"    <path d="{path}"/>"#)?;
+        }
+        writeln!(svg, "  </g>")?;
+        writeln!(
+            svg,
+            r##"  <g id="border" inkscape:groupmode="layer" inkscape:label="border" fill="none" stroke="#ff0000" stroke-width="{}">"##,
+            options.stroke_width
+        )?;
+        for polygon in paths.border.iter() {
+            let mut path = String::new();
+            write_move_to(&mut path, polygon[0]);
+            for point in polygon.iter().skip(1) {
+                write!(
+                    path,
+                    " L{} {}",
+                    round(point.x as f32),
+                    round(point.y as f32)
+                )?;
+            }
+            path.push_str(" Z");
+            writeln!(svg, r#"    <path d="{path}"/>"#)?;
+        }
+        writeln!(svg, "  </g>")?;
+        writeln!(svg, "</svg>")?;
+        Ok(svg)
+    }
+
+    /// Serialises the template into an AutoCAD R12 DXF document measured in millimetres. The
+    /// curves are flattened into polylines, the inner edges are in the `CUT` layer and the outer
+    /// border in the `BORDER` layer. The y axis points up, as usual for DXF.
+    ///
+    /// R12 has no header variable for the drawing units, so the importing program has to be set
+    /// to millimetres.
+    ///
+    /// Returns an error if the DPI are not a positive number.
+    pub fn to_dxf(&self, options: &LaserOptions) -> JigsawResult<String> {
+        let paths = self.laser_paths(options)?;
+        let mut dxf = String::new();
+        for (code, value) in [
+            (0, "SECTION"),
+            (2, "HEADER"),
+            (9, "$ACADVER"),
+            (1, "AC1009"),
+            (0, "ENDSEC"),
+            (0, "SECTION"),
+            (2, "TABLES"),
+            (0, "TABLE"),
+            (2, "LAYER"),
+            (70, "2"),
+        ] {
+            write_group(&mut dxf, code, value)?;
+        }
+        for (layer, color) in [("CUT", "7"), ("BORDER", "1")] {
+            for (code, value) in [
+                (0, "LAYER"),
+                (2, layer),
+                (70, "0"),
+                (62, color),
+                (6, "CONTINUOUS"),
+            ] {
+                write_group(&mut dxf, code, value)?;
+            }
+        }
+        for (code, value) in [
+            (0, "ENDTAB"),
+            (0, "ENDSEC"),
+            (0, "SECTION"),
+            (2, "ENTITIES"),
+        ] {
+            write_group(&mut dxf, code, value)?;
+        }
+        for beziers in paths.cuts.iter() {
+            let points = raster::flatten_open(beziers);
+            write_polyline(&mut dxf, "CUT", &points, false, paths.height)?;
+        }
+        for polygon in paths.border.iter() {
+            write_polyline(&mut dxf, "BORDER", polygon, true, paths.height)?;
+        }
+        write_group(&mut dxf, 0, "ENDSEC")?;
+        write_group(&mut dxf, 0, "EOF")?;
+        Ok(dxf)
+    }
+
+    /// Returns the inner edges and the offset border of the template in millimetres
+    fn laser_paths(&self, options: &LaserOptions) -> JigsawResult<LaserPaths> {
+        if !(options.dpi.is_finite() && options.dpi > 0.0) {
+            return Err(JigsawError::InvalidDpi(options.dpi));
+        }
+        let scale = MM_PER_INCH / options.dpi as f64;
+        let to_mm = |point: DVec2| point * scale;
+
+        let mut owners: HashMap<_, usize> = HashMap::new();
+        for edge in self.pieces.iter().flat_map(|piece| piece.edges.iter()) {
+            *owners.entry(edge_key(edge)).or_default() += 1;
+        }
+        let (border_edges, cut_edges): (Vec<_>, Vec<_>) = self
+            .unique_edges()
+            .into_iter()
+            .partition(|edge| owners[&edge_key(edge)] == 1);
+
+        let cuts = cut_edges
+            .iter()
+            .map(|edge| {
+                edge.to_beziers(false)
+                    .iter()
+                    .map(|bezier| bezier.apply_transformation(to_mm))
+                    .collect()
+            })
+            .collect();
+        let border_lines = border_edges
+            .iter()
+            .map(|edge| raster::flatten_open(&edge.to_beziers(false)))
+            .collect();
+        let offset = options.kerf as f64 / 2.0 / scale;
+        let border = join_lines(border_lines)
+            .into_iter()
+            .map(|(points, closed)| {
+                let points = if closed {
+                    offset_polygon(&points, offset)
+                } else {
+                    points
+                };
+                points.into_iter().map(to_mm).collect()
+            })
+            .collect();
+
+        let (width, height) = self.image_dimensions();
+        Ok(LaserPaths {
+            width: width as f64 * scale,
+            height: height as f64 * scale,
+            cuts,
+            border,
+        })
+    }
+}
+
+/// Joins polylines with matching end points into chains. Returns every chain with whether it
+/// is closed, the last point of a closed chain isn't repeated.
+fn join_lines(mut lines: Vec<Vec<DVec2>>) -> Vec<(Vec<DVec2>, bool)> {
+    lines.retain(|line| line.len() > 1);
+    let mut chains = vec![];
+    while let Some(mut chain) = lines.pop() {
+        let closed = loop {
+            let end = chain[chain.len() - 1];
+            if chain.len() > 2 && end.distance(chain[0]) < JOIN_TOLERANCE {
+                chain.pop();
+                break true;
+            }
+            let Some(index) = lines.iter().position(|line| {
+                line[0].distance(end) < JOIN_TOLERANCE
+                    || line[line.len() - 1].distance(end) < JOIN_TOLERANCE
+            }) else {
+                break false;
+            };
+            let mut line = lines.swap_remove(index);
+            if line[0].distance(end) >= JOIN_TOLERANCE {
+                line.reverse();
+            }
+            chain.extend(line.into_iter().skip(1));
+        };
+        chain.dedup_by(|a, b| a.distance(*b) < f64::EPSILON);
+        chains.push((chain, closed));
+    }
+    chains
+}
+
+/// Moves every side of the closed polygon outwards by the given distance, or inwards for a
+/// negative one, with mitered corners
+fn offset_polygon(polygon: &[DVec2], distance: f64) -> Vec<DVec2> {
+    let count = polygon.len();
+    if distance == 0.0 || count < 3 {
+        return polygon.to_vec();
+    }
+    let area: f64 = polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum();
+    // the inside is on the left of the sides of a polygon with a positive area
+    let outwards = area.signum();
+    let normal = |a: DVec2, b: DVec2| {
+        let direction = (b - a).normalize_or_zero();
+        DVec2::new(direction.y, -direction.x) * outwards
+    };
+    (0..count)
+        .map(|i| {
+            let previous = polygon[(i + count - 1) % count];
+            let point = polygon[i];
+            let next = polygon[(i + 1) % count];
+            let (before, after) = (normal(previous, point), normal(point, next));
+            let miter = (before + after).normalize_or_zero();
+            point + miter * distance / miter.dot(before).max(MIN_MITER_COS)
+        })
+        .collect()
+}
+
+/// Writes a DXF group of a code and a value
+fn write_group(dxf: &mut String, code: u32, value: impl Display) -> std::fmt::Result {
+    writeln!(dxf, "{code}\n{value}")
+}
+
+/// Writes a DXF polyline entity. The y coordinates are flipped at the given height.
+fn write_polyline(
+    dxf: &mut String,
+    layer: &str,
+    points: &[DVec2],
+    closed: bool,
+    height: f64,
+) -> std::fmt::Result {
+    write_group(dxf, 0, "POLYLINE")?;
+    write_group(dxf, 8, layer)?;
+    write_group(dxf, 66, 1)?;
+    write_group(dxf, 70, u8::from(closed))?;
+    for code in [10, 20, 30] {
+        write_group(dxf, code, "0.0")?;
+    }
+    for point in points {
+        write_group(dxf, 0, "VERTEX")?;
+        write_group(dxf, 8, layer)?;
+        write_group(dxf, 10, format_args!("{:.4}", point.x))?;
+        write_group(dxf, 20, format_args!("{:.4}", height - point.y))?;
+        write_group(dxf, 30, "0.0")?;
+    }
+    write_group(dxf, 0, "SEQEND")?;
+    write_group(dxf, 8, layer)
+}
@@ -2,41 +2,103 @@
 //! given rectangular image. It provides three public functions:
 //!
 //! - [`JigsawGenerator::generate`] returns the paths from a given number of pieces in a column and a
-//!     row. This is the function you normally want to use
+//!   row. This is the function you normally want to use
 //! - [`generate_columns_rows_numbers`] returns an ideal distribution of pieces on the x- and y-axes
-//!     for a given total number of pieces
+//!   for a given total number of pieces. [`JigsawGenerator::with_target_pieces`] creates a
+//!   generator with this distribution
 //! - [`round`] is a util function which approximately rounds a f32 value to two decimal places
+//!
+//! A generated [`JigsawTemplate`] can be serialised to an SVG document with
+//! [`JigsawTemplate::to_svg`], or for laser cutters in millimetres with
+//! [`JigsawTemplate::to_laser_svg`] and [`JigsawTemplate::to_dxf`]. [`JigsawTemplate::label_map`]
+//! rasterises all pieces into a map of the piece owning every pixel, and
+//! [`JigsawTemplate::verify`] checks that the pieces tile the image. [`JigsawTemplate::pack_atlas`]
+//! crops all pieces into a few texture atlas pages instead of one image per piece, and
+//! [`JigsawTemplate::scaled`] rescales a template, e.g. from a small proxy image to the original.
+//! With the `serde` feature enabled, templates and pieces implement `Serialize` and `Deserialize`
+//! so a cut layout can be stored and loaded without the image.
+//!
+//! The shape of the tabs is pluggable: pass one of the built-in [`EdgeProfile`]s or your own
+//! implementation to [`JigsawGenerator::edge_profile`].
+//!
+//! With [`JigsawGenerator::wrap_around`] the right border of the image tabs into the left one and
+//! the bottom border into the top one, for puzzles without any border pieces.
 
-use anyhow::{anyhow, Result};
 use bezier_rs::{Bezier, BezierHandles, Identifier, Subpath};
 use glam::DVec2;
+use image::imageops::FilterType;
 use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
 
 use log::{debug, info, trace};
+#[cfg(feature = "parallel")]
 use rayon::iter::ParallelIterator;
-use std::{sync::Arc, vec};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc, vec};
 
+pub use atlas::{AtlasLayout, AtlasOptions, AtlasPage, AtlasRect};
+pub use error::{JigsawError, JigsawResult};
 pub use image;
 pub use imageproc;
+pub use label::{LabelMap, NO_PIECE, OVERLAPPING_PIECES};
+pub use laser::LaserOptions;
+pub use profile::{
+    ArrowProfile, ClassicProfile, DoubleTabProfile, EdgeProfile, NubProfile, ProfileSegment,
+    TabShape, WavyProfile,
+};
 use rand::random;
+pub use shading::BevelOptions;
+pub use svg::SvgOptions;
+use wrap::shared_edge_key;
+pub use wrap::WrapAround;
+
+mod atlas;
+mod error;
+mod hexagon;
+mod label;
+mod laser;
+mod profile;
+mod raster;
+mod scale;
+mod shading;
+mod silhouette;
+mod svg;
+mod untangle;
+mod verify;
+mod voronoi;
+mod wrap;
 
 const DEFAULT_TAB_SIZE: f32 = 20.0;
 const DEFAULT_JITTER: f32 = 5.0;
+const DEFAULT_WARP: f32 = 0.0;
+
+/// The minimum width and height of a piece in pixel
+pub const MIN_PIECE_SIZE: f32 = 8.0;
 
 const MAX_WIDTH: u32 = 1920;
 const MAX_HEIGHT: u32 = 1200;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GameMode {
+    /// Rectangular pieces with a tab or a blank on every inner edge
     #[default]
     Classic,
+    /// Rectangular pieces with straight edges
     Square,
+    /// Hexagonal pieces on an offset grid with a tab or a blank on every inner edge. Every other
+    /// row is shifted by half a piece and starts and ends with a half piece.
+    Hexagon,
+    /// Irregular polygonal pieces from a relaxed Voronoi diagram with one jittered seed point per
+    /// grid cell. Every border shared by two pieces has a tab or a blank.
+    Irregular,
 }
 
 /// A segment of an indented puzzle piece edge. A segment is described by a cubic Bézier curve,
 /// which includes a starting point, an end point and two control points. Three segments make up a
 /// piece's edge.
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IndentationSegment {
     /// Starting point of the segment
     pub starting_point: (f32, f32),
@@ -77,15 +139,15 @@ impl IndentationSegment {
 }
 
 #[derive(Clone, PartialEq, Debug)]
-/// An indented puzzle piece edge. An edge is decribe via three distinct cubic Bézier curves (the
-/// "segments")
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// An indented puzzle piece edge. An edge is described via a chain of cubic Bézier curves (the
+/// "segments") whose shape is given by an [`EdgeProfile`]
 pub struct IndentedEdge {
-    /// Describes the left half for a horizontal edge, the upper half for a vertical edge
-    pub first_segment: IndentationSegment,
-    /// Describes the form of the tab
-    pub middle_segment: IndentationSegment,
-    /// Describes the right half for a horizontal edge, the lower half for a vertical edge
-    pub last_segment: IndentationSegment,
+    /// The segments from the starting to the end point, at least one. The default
+    /// [`ClassicProfile`] uses three of them: the left (upper) half, the tab and the right
+    /// (lower) half of the edge.
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "non_empty_segments"))]
+    pub segments: Vec<IndentationSegment>,
 }
 
 #[allow(dead_code)]
@@ -103,23 +165,25 @@ impl IndentedEdge {
         starting_point: (f32, f32),
         end_point: (f32, f32),
         generator: &mut EdgeContourGenerator,
-    ) -> Self {
+    ) -> JigsawResult<Self> {
         generator.create(starting_point, end_point)
     }
 
+    /// Creates a new indented edge of any direction, see [`EdgeContourGenerator::create_along`]
+    pub fn new_along(
+        starting_point: (f32, f32),
+        end_point: (f32, f32),
+        generator: &mut EdgeContourGenerator,
+    ) -> JigsawResult<Self> {
+        generator.create_along(starting_point, end_point)
+    }
+
     pub fn to_beziers(&self, reverse: bool) -> Vec<Bezier> {
+        let beziers = self.segments.iter().map(|s| s.to_bezier(reverse));
         if reverse {
-            vec![
-                self.last_segment.to_bezier(reverse),
-                self.middle_segment.to_bezier(reverse),
-                self.first_segment.to_bezier(reverse),
-            ]
+            beziers.rev().collect()
         } else {
-            vec![
-                self.first_segment.to_bezier(reverse),
-                self.middle_segment.to_bezier(reverse),
-                self.last_segment.to_bezier(reverse),
-            ]
+            beziers.collect()
         }
     }
 }
@@ -135,8 +199,8 @@ pub struct EdgeContourGenerator {
     tab_size: f32,
     /// The "jitter" factor. A bigger number makes the puzzle pieces more asymmetrical
     jitter: f32,
-    /// Seed for random values. Increased by 1 after each iteration.
-    seed: usize,
+    /// Counter for random values. Starts at the seed and is advanced after each draw.
+    seed: u64,
     /// Flipped tab
     flipped: bool,
     /// Random value based on the seed and the predefined jitter value.
@@ -145,26 +209,30 @@ pub struct EdgeContourGenerator {
     c: f32,
     d: f32,
     e: f32,
+    /// Factor the tabs and their jitter are scaled by, to shrink tabs that collide with other
+    /// edges
+    tab_scale: f32,
+    /// The shape of the tabs
+    profile: Arc<dyn EdgeProfile>,
 }
 
 impl EdgeContourGenerator {
     /// Creates a new [`EdgeContourGenerator`] instance after making sure that the optionally
-    /// provided `tab_size`, `jitter` and `seed` values are in the allowed ranges
+    /// provided `tab_size` and `jitter` values are in the allowed ranges
     pub fn new(
         piece_width: f32,
         piece_height: f32,
         tab_size: Option<f32>,
         jitter: Option<f32>,
         seed: Option<usize>,
-    ) -> EdgeContourGenerator {
-        let tab_size = tab_size.unwrap_or(DEFAULT_TAB_SIZE) / 200.0;
-        assert!((0.05..=0.15).contains(&tab_size));
-        let jitter = jitter.unwrap_or(DEFAULT_JITTER) / 100.0;
-        assert!((0.0..=0.13).contains(&jitter));
-        let seed = seed.unwrap_or(0);
-        let e = Self::uniform(-jitter, jitter, seed + 1);
-        let (seed, flipped, a, b, c, d, e) = Self::dice(e, false, seed + 2, jitter);
-        EdgeContourGenerator {
+    ) -> JigsawResult<EdgeContourGenerator> {
+        let tab_size = check_tab_size(tab_size)? / 200.0;
+        let jitter = check_jitter(jitter)? / 100.0;
+        // scramble the seed so that neighbouring seeds don't yield shifted copies of one sequence
+        let seed = Self::mix(seed.unwrap_or(0) as u64);
+        let e = Self::uniform(-jitter, jitter, seed.wrapping_add(1));
+        let (seed, flipped, a, b, c, d, e) = Self::dice(e, false, seed.wrapping_add(2), jitter);
+        Ok(EdgeContourGenerator {
             piece_width,
             piece_height,
             tab_size,
@@ -176,41 +244,56 @@ impl EdgeContourGenerator {
             c,
             d,
             e,
-        }
+            tab_scale: 1.0,
+            profile: Arc::new(ClassicProfile),
+        })
     }
 
-    /// Normalises the seed value on a scale between 0 and 1
-    fn normalise(seed: usize) -> f32 {
-        let x = f32::sin(seed as f32) * 10000.0;
-        x - f32::floor(x)
+    /// Sets the [`EdgeProfile`] of the generated edges
+    pub fn profile(mut self, profile: Arc<dyn EdgeProfile>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// The SplitMix64 output function. Scrambles a counter value into a pseudo-random 64 bit
+    /// value using integer arithmetic only.
+    fn mix(seed: u64) -> u64 {
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Maps a counter value to a pseudo-random number in `[0, 1)`.
+    ///
+    /// The upper 24 bits of the scrambled counter are converted exactly into a float, so the same
+    /// counter yields bit-identical values on every target, independent of the platform's libm
+    /// and of the size of the seed.
+    fn normalise(seed: u64) -> f32 {
+        (Self::mix(seed) >> 40) as f32 / (1u64 << 24) as f32
     }
 
     /// Returns a statistically evenly distributed value between a `min` and a `max` value
-    fn uniform(min: f32, max: f32, seed: usize) -> f32 {
+    fn uniform(min: f32, max: f32, seed: u64) -> f32 {
         min + Self::normalise(seed) * (max - min)
     }
 
     /// Returns `true` if the given value is greater than 0.5 after being normalised on a scale
     /// between 0.0 and 1.0. I.e. the chances should be approximately 50% for the result to be
     /// `true`.
-    fn rbool(seed: usize) -> bool {
+    fn rbool(seed: u64) -> bool {
         Self::normalise(seed) > 0.5
     }
 
     /// Recomputes the factors influencing the form of the edge
-    fn dice(
-        e: f32,
-        flipped: bool,
-        seed: usize,
-        jitter: f32,
-    ) -> (usize, bool, f32, f32, f32, f32, f32) {
+    fn dice(e: f32, flipped: bool, seed: u64, jitter: f32) -> (u64, bool, f32, f32, f32, f32, f32) {
         let new_flipped = Self::rbool(seed);
         let a = if new_flipped == flipped { -e } else { e };
-        let b = Self::uniform(-jitter, jitter, seed + 2);
-        let c = Self::uniform(-jitter, jitter, seed + 3);
-        let d = Self::uniform(-jitter, jitter, seed + 4);
-        let e = Self::uniform(-jitter, jitter, seed + 5);
-        (seed + 6, new_flipped, a, b, c, d, e)
+        let b = Self::uniform(-jitter, jitter, seed.wrapping_add(2));
+        let c = Self::uniform(-jitter, jitter, seed.wrapping_add(3));
+        let d = Self::uniform(-jitter, jitter, seed.wrapping_add(4));
+        let e = Self::uniform(-jitter, jitter, seed.wrapping_add(5));
+        (seed.wrapping_add(6), new_flipped, a, b, c, d, e)
     }
 
     /// Computes the position of a point on an axis along the piece's edge
@@ -224,16 +307,29 @@ impl EdgeContourGenerator {
     }
 
     /// Gets the coordinates of a point in a cubic Bézier curve relative to a starting point, the
-    /// length and the side of the edge (horizontal, vertical) and finally two coefficients
-    /// which designate the offset of the respective points on the longitudinal (`l_coeff`) and the
-    /// transverse (`t_coeff`) axes.
+    /// orientation of the edge (see [`EdgeFrame`]) and finally two coefficients which designate
+    /// the offset of the respective points on the longitudinal (`l_coeff`) and the transverse
+    /// (`t_coeff`) axes.
     fn coords(
         &self,
         l_coeff: f32,
         t_coeff: f32,
         starting_point: (f32, f32),
-        vertical: bool,
+        frame: EdgeFrame,
     ) -> (f32, f32) {
+        let vertical = match frame {
+            EdgeFrame::Horizontal => false,
+            EdgeFrame::Vertical => true,
+            EdgeFrame::Oblique { length, direction } => {
+                let sign = if self.flipped { -1.0 } else { 1.0 };
+                let l = l_coeff * length;
+                let t = t_coeff * length * sign;
+                return (
+                    round(starting_point.0 + l * direction.0 - t * direction.1),
+                    round(starting_point.1 + l * direction.1 + t * direction.0),
+                );
+            }
+        };
         let pos_1 = Self::longitudinal_position(
             l_coeff,
             if vertical {
@@ -268,102 +364,91 @@ impl EdgeContourGenerator {
         }
     }
 
-    /// Coordinates of the first segment's end point
-    fn ep1(&self, starting_point: (f32, f32), vertical: bool) -> (f32, f32) {
-        self.coords(
-            0.5 - self.tab_size + self.b,
-            self.tab_size + self.c,
-            starting_point,
-            vertical,
-        )
-    }
-
-    /// Coordinates of the first segment's first control point
-    fn cp1_1(&self, starting_point: (f32, f32), vertical: bool) -> (f32, f32) {
-        self.coords(0.2, self.a, starting_point, vertical)
-    }
-
-    /// Coordinates of the first segment's second control point
-    fn cp1_2(&self, starting_point: (f32, f32), vertical: bool) -> (f32, f32) {
-        self.coords(
-            0.5 + self.b + self.d,
-            -self.tab_size + self.c,
-            starting_point,
-            vertical,
-        )
-    }
-
-    /// Coordinates of the second segment's end point
-    fn ep2(&self, starting_point: (f32, f32), vertical: bool) -> (f32, f32) {
-        self.coords(
-            0.5 + self.tab_size + self.b,
-            self.tab_size + self.c,
-            starting_point,
-            vertical,
-        )
-    }
-
-    /// Coordinates of the second segment's first control point
-    fn cp2_1(&self, starting_point: (f32, f32), vertical: bool) -> (f32, f32) {
-        self.coords(
-            0.5 - 2.0 * self.tab_size + self.b - self.d,
-            3.0 * self.tab_size + self.c,
-            starting_point,
-            vertical,
-        )
-    }
-
-    /// Coordinates of the second segment's second control point
-    fn cp2_2(&self, starting_point: (f32, f32), vertical: bool) -> (f32, f32) {
-        self.coords(
-            0.5 + 2.0 * self.tab_size + self.b - self.d,
-            3.0 * self.tab_size + self.c,
-            starting_point,
-            vertical,
-        )
-    }
-
-    /// Coordinates of the third segment's first control point
-    fn cp3_1(&self, starting_point: (f32, f32), vertical: bool) -> (f32, f32) {
-        self.coords(
-            0.5 + self.b + self.d,
-            -self.tab_size + self.b + self.d,
-            starting_point,
-            vertical,
-        )
+    /// Returns a new [`IndentedEdge`] from a given starting and end point. The edge is expected to
+    /// be horizontal or vertical, and its tab is sized by the baseline piece width and height.
+    /// Fails if the [`EdgeProfile`] returns no segments.
+    pub fn create(
+        &mut self,
+        starting_point: (f32, f32),
+        end_point: (f32, f32),
+    ) -> JigsawResult<IndentedEdge> {
+        let frame = if (end_point.0 - starting_point.0).abs() < 1.0 {
+            EdgeFrame::Vertical
+        } else {
+            EdgeFrame::Horizontal
+        };
+        self.create_in_frame(starting_point, end_point, frame)
     }
 
-    /// Coordinates of the third segment's second control point
-    fn cp3_2(&self, starting_point: (f32, f32), vertical: bool) -> (f32, f32) {
-        self.coords(0.8, self.e, starting_point, vertical)
+    /// Returns a new [`IndentedEdge`] between two arbitrary points. Unlike
+    /// [`EdgeContourGenerator::create`] the tab is laid out along the line between the points and
+    /// sized by the length of that line, so the edge may have any direction and length.
+    pub fn create_along(
+        &mut self,
+        starting_point: (f32, f32),
+        end_point: (f32, f32),
+    ) -> JigsawResult<IndentedEdge> {
+        let (dx, dy) = (
+            end_point.0 - starting_point.0,
+            end_point.1 - starting_point.1,
+        );
+        let length = (dx * dx + dy * dy).sqrt();
+        let frame = EdgeFrame::Oblique {
+            length,
+            direction: (dx / length, dy / length),
+        };
+        self.create_in_frame(starting_point, end_point, frame)
     }
 
-    /// Returns a new [`IndentedEdge`] from a given starting and end point
-    pub fn create(&mut self, starting_point: (f32, f32), end_point: (f32, f32)) -> IndentedEdge {
-        let vertical = (end_point.0 - starting_point.0).abs() < 1.0;
-        let first_segment = IndentationSegment {
-            starting_point,
-            end_point: self.ep1(starting_point, vertical),
-            control_point_1: self.cp1_1(starting_point, vertical),
-            control_point_2: self.cp1_2(starting_point, vertical),
-        };
-        let middle_segment = IndentationSegment {
-            starting_point: self.ep1(starting_point, vertical),
-            end_point: self.ep2(starting_point, vertical),
-            control_point_1: self.cp2_1(starting_point, vertical),
-            control_point_2: self.cp2_2(starting_point, vertical),
-        };
-        let last_segment = IndentationSegment {
-            starting_point: self.ep2(starting_point, vertical),
-            end_point,
-            control_point_1: self.cp3_1(starting_point, vertical),
-            control_point_2: self.cp3_2(starting_point, vertical),
-        };
-        let indented_edge = IndentedEdge {
-            first_segment,
-            middle_segment,
-            last_segment,
+    fn create_in_frame(
+        &mut self,
+        starting_point: (f32, f32),
+        end_point: (f32, f32),
+        frame: EdgeFrame,
+    ) -> JigsawResult<IndentedEdge> {
+        let scale = self.tab_scale;
+        let shape = TabShape {
+            tab_size: self.tab_size * scale,
+            a: self.a * scale,
+            b: self.b * scale,
+            c: self.c * scale,
+            d: self.d * scale,
+            e: self.e * scale,
         };
+        let profile_segments = self.profile.segments(&shape);
+        if profile_segments.is_empty() {
+            return Err(JigsawError::EmptyProfile);
+        }
+        let last = profile_segments.len().saturating_sub(1);
+        let mut segments = vec![];
+        let mut segment_start = starting_point;
+        for (i, [control_point_1, control_point_2, end]) in profile_segments.into_iter().enumerate()
+        {
+            // the last segment ends exactly at the given end point instead of a rounded one
+            let segment_end = if i == last {
+                end_point
+            } else {
+                self.coords(end.0, end.1, starting_point, frame)
+            };
+            segments.push(IndentationSegment {
+                starting_point: segment_start,
+                end_point: segment_end,
+                control_point_1: self.coords(
+                    control_point_1.0,
+                    control_point_1.1,
+                    starting_point,
+                    frame,
+                ),
+                control_point_2: self.coords(
+                    control_point_2.0,
+                    control_point_2.1,
+                    starting_point,
+                    frame,
+                ),
+            });
+            segment_start = segment_end;
+        }
+        let indented_edge = IndentedEdge { segments };
         (
             self.seed,
             self.flipped,
@@ -372,12 +457,29 @@ impl EdgeContourGenerator {
             self.c,
             self.d,
             self.e,
-        ) = Self::dice(self.e, false, self.seed + 2, self.jitter);
-        indented_edge
+        ) = Self::dice(self.e, false, self.seed.wrapping_add(2), self.jitter);
+        Ok(indented_edge)
     }
 }
 
+/// The orientation of an edge, which determines how the tab coefficients of an
+/// [`EdgeContourGenerator`] are mapped onto the image plane
+#[derive(Debug, Clone, Copy)]
+enum EdgeFrame {
+    /// A horizontal edge, scaled by the baseline piece width (along) and height (across)
+    Horizontal,
+    /// A vertical edge, scaled by the baseline piece height (along) and width (across)
+    Vertical,
+    /// An edge of any direction, scaled by its own length
+    Oblique {
+        length: f32,
+        /// The unit vector from the starting to the end point
+        direction: (f32, f32),
+    },
+}
+
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 /// A puzzle piece edge which is at the same time a part of the puzzle's border and therefore forms
 /// a straight line
 pub struct StraightEdge {
@@ -406,6 +508,7 @@ impl StraightEdge {
 }
 
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 /// A border of a puzzle piece. Can be either an `StraightEdge` (no adjacent other piece) or an
 /// `IndentedEdge`
 pub enum Edge {
@@ -420,6 +523,36 @@ impl Edge {
             Edge::StraightEdge(oe) => oe.to_beziers(reverse),
         }
     }
+
+    /// Starting point of the edge
+    pub fn starting_point(&self) -> (f32, f32) {
+        match self {
+            Edge::IndentedEdge(ie) => ie.segments[0].starting_point,
+            Edge::StraightEdge(se) => se.starting_point,
+        }
+    }
+
+    /// End point of the edge
+    pub fn end_point(&self) -> (f32, f32) {
+        match self {
+            Edge::IndentedEdge(ie) => ie.segments[ie.segments.len() - 1].end_point,
+            Edge::StraightEdge(se) => se.end_point,
+        }
+    }
+}
+
+/// Returns a new edge of the classic lattice. Edges on the regular lattice keep the tab
+/// proportions of the baseline piece size, warped ones are laid out along their own direction.
+fn lattice_edge(
+    starting_point: (f32, f32),
+    end_point: (f32, f32),
+    contour_gen: &mut EdgeContourGenerator,
+) -> JigsawResult<IndentedEdge> {
+    if starting_point.0 == end_point.0 || starting_point.1 == end_point.1 {
+        IndentedEdge::new(starting_point, end_point, contour_gen)
+    } else {
+        IndentedEdge::new_along(starting_point, end_point, contour_gen)
+    }
 }
 
 /// Divides the axis into `pieces` of equal length. Returns the starting point of each piece,
@@ -435,6 +568,36 @@ fn divide_axis(length: f32, piece_num: usize) -> (Vec<f32>, f32) {
     )
 }
 
+/// Returns the tab size or its default value if it is between `10.0` and `30.0`
+fn check_tab_size(tab_size: Option<f32>) -> JigsawResult<f32> {
+    let tab_size = tab_size.unwrap_or(DEFAULT_TAB_SIZE);
+    if (10.0..=30.0).contains(&tab_size) {
+        Ok(tab_size)
+    } else {
+        Err(JigsawError::InvalidTabSize(tab_size))
+    }
+}
+
+/// Returns the jitter or its default value if it is between `0.0` and `13.0`
+fn check_jitter(jitter: Option<f32>) -> JigsawResult<f32> {
+    let jitter = jitter.unwrap_or(DEFAULT_JITTER);
+    if (0.0..=13.0).contains(&jitter) {
+        Ok(jitter)
+    } else {
+        Err(JigsawError::InvalidJitter(jitter))
+    }
+}
+
+/// Returns the warp or its default value if it is between `0.0` and `25.0`
+fn check_warp(warp: Option<f32>) -> JigsawResult<f32> {
+    let warp = warp.unwrap_or(DEFAULT_WARP);
+    if (0.0..=25.0).contains(&warp) {
+        Ok(warp)
+    } else {
+        Err(JigsawError::InvalidWarp(warp))
+    }
+}
+
 /// Rounds a given rational number to two decimal places
 pub fn round(x: f32) -> f32 {
     (x * 100.0).round() / 100.0
@@ -453,16 +616,9 @@ fn get_border_indices(position: usize, number_of_columns: usize) -> (usize, usiz
     )
 }
 
-/// Returns the position of a given segment's end
-fn end_point_pos(ind: usize, segments: &[f32], fallback: f32) -> f32 {
-    if ind < (segments.len() - 1) {
-        segments[ind + 1]
-    } else {
-        fallback
-    }
-}
-
 /// Returns all divisor pairs for a given number
+// `usize::is_multiple_of` needs Rust 1.87
+#[allow(clippy::manual_is_multiple_of)]
 fn find_divisors(num: usize) -> Vec<(usize, usize)> {
     let mut i = 1;
     let mut divisor_pairs = vec![];
@@ -490,11 +646,12 @@ fn optimal_aspect_ratio(
     possible_dimensions: Vec<(usize, usize)>,
     image_width: f32,
     image_height: f32,
-) -> Result<(usize, usize)> {
+) -> JigsawResult<(usize, usize)> {
     let mut width_height_diff = f32::MAX;
-    let mut number_of_pieces = *possible_dimensions
-        .first()
-        .ok_or_else(|| anyhow!("No possible dimensions found"))?;
+    let mut number_of_pieces = *possible_dimensions.first().ok_or(JigsawError::ZeroPieces {
+        pieces_in_column: 0,
+        pieces_in_row: 0,
+    })?;
     for (x, y) in possible_dimensions {
         let width = image_width / x as f32;
         let height = image_height / y as f32;
@@ -518,7 +675,7 @@ pub fn generate_columns_rows_numbers(
     image_width: f32,
     image_height: f32,
     number_of_pieces: usize,
-) -> Result<(usize, usize)> {
+) -> JigsawResult<(usize, usize)> {
     let divisor_pairs = find_divisors(number_of_pieces);
     optimal_aspect_ratio(divisor_pairs, image_width, image_height)
 }
@@ -532,10 +689,25 @@ pub fn generate_columns_rows_numbers(
 /// The `tab_size` argument defines the size of the pieces' tabs. It can be any number from `10.0` to `30.0` and defaults to `20.0`
 ///
 /// `jitter` can be a number between 0.0 and 13.0. The bigger the number, the more asymmetrical are
-/// the puzzle pieces. Defaults to `0.0` (symmetrical).
+/// the puzzle pieces. Defaults to `5.0`.
 ///
-/// `seed` provides the initial "randomness" when creating the contours of the puzzle pieces. Same
-/// seed values for images with same dimensions and same number of pieces lead to same SVG paths.
+/// `warp` can be a number between 0.0 and 25.0. It shifts every piece corner of the
+/// [`GameMode::Classic`] and [`GameMode::Square`] grids by up to this percentage of the piece
+/// width and height, so the rows and columns no longer line up and the pieces vary in size.
+/// Corners on the image border only move along the border. Defaults to `0.0`.
+///
+/// `seed` provides the initial "randomness" when creating the contours of the puzzle pieces. Every
+/// constructor picks a random seed, call [`JigsawGenerator::seed`] to make the output
+/// reproducible. All random values are drawn from a counter-based SplitMix64 generator using
+/// integer arithmetic only, and the contours are computed with plain IEEE 754 operations, so the
+/// same image dimensions, number of pieces, seed, tab size and jitter lead to byte-identical edges
+/// on every platform.
+///
+/// The pieces are cut from the image as it is, or from a resized copy:
+/// [`JigsawGenerator::crop_to_aspect_ratio`] cuts the center of the image to the given aspect
+/// ratio first, [`JigsawGenerator::target_size`] then scales it to an exact size. Otherwise
+/// `generate` scales the image down to the [`JigsawGenerator::max_dimensions`] if asked to. The
+/// [`JigsawGenerator::resampling`] filter is used for every scaling.
 #[derive(Debug, Clone)]
 pub struct JigsawGenerator {
     /// The original image from which the jigsaw puzzle pieces will be generated.
@@ -548,8 +720,24 @@ pub struct JigsawGenerator {
     tab_size: Option<f32>,
     /// Optional jitter factor to introduce asymmetry in the puzzle pieces.
     jitter: Option<f32>,
+    /// Optional warp factor to shift the corners of the pieces off the regular grid.
+    warp: Option<f32>,
+    /// The shape of the tabs on the puzzle pieces.
+    edge_profile: Arc<dyn EdgeProfile>,
     /// Optional seed value for randomness in generating the puzzle pieces.
     seed: Option<usize>,
+    /// The maximum width and height of a resized image.
+    max_dimensions: (u32, u32),
+    /// Optional exact width and height of the image.
+    target_size: Option<(u32, u32)>,
+    /// The filter to scale the image with.
+    resampling: FilterType,
+    /// Optional aspect ratio to cut the center of the image to.
+    aspect_ratio: Option<f32>,
+    /// Optional alpha value up to which pixels are transparent background.
+    alpha_threshold: Option<u8>,
+    /// Whether the pieces on the right and bottom border continue on the left and top border.
+    wrap_around: bool,
 }
 
 impl JigsawGenerator {
@@ -560,7 +748,15 @@ impl JigsawGenerator {
             pieces_in_row,
             tab_size: None,
             jitter: None,
+            warp: None,
+            edge_profile: Arc::new(ClassicProfile),
             seed: Some(random()),
+            max_dimensions: (MAX_WIDTH, MAX_HEIGHT),
+            target_size: None,
+            resampling: FilterType::Lanczos3,
+            aspect_ratio: None,
+            alpha_threshold: None,
+            wrap_around: false,
         }
     }
 
@@ -570,10 +766,15 @@ impl JigsawGenerator {
         image_bytes: &[u8],
         pieces_in_column: usize,
         pieces_in_row: usize,
-    ) -> Result<Self> {
+    ) -> JigsawResult<Self> {
         let origin_image = DynamicImage::ImageRgba8(
-            RgbaImage::from_raw(width, height, image_bytes.to_vec())
-                .ok_or_else(|| anyhow!("Failed to create image from raw bytes"))?,
+            RgbaImage::from_raw(width, height, image_bytes.to_vec()).ok_or(
+                JigsawError::InvalidRawBuffer {
+                    width,
+                    height,
+                    len: image_bytes.len(),
+                },
+            )?,
         );
         Ok(JigsawGenerator::new(
             origin_image,
@@ -582,13 +783,32 @@ impl JigsawGenerator {
         ))
     }
 
+    /// Creates a new `JigsawGenerator` instance which cuts the image into `number_of_pieces`
+    /// pieces. The numbers of pieces in a column and a row are chosen for the most square pieces
+    /// on this image, see [`generate_columns_rows_numbers`].
+    pub fn with_target_pieces(
+        origin_image: DynamicImage,
+        number_of_pieces: usize,
+    ) -> JigsawResult<Self> {
+        let (pieces_in_column, pieces_in_row) = generate_columns_rows_numbers(
+            origin_image.width() as f32,
+            origin_image.height() as f32,
+            number_of_pieces,
+        )?;
+        Ok(JigsawGenerator::new(
+            origin_image,
+            pieces_in_column,
+            pieces_in_row,
+        ))
+    }
+
     /// Creates a new `JigsawGenerator` instance from an image file at the given `image_path`
     /// with a given number of pieces in a column and a row.
     pub fn from_path(
         image_path: &str,
         pieces_in_column: usize,
         pieces_in_row: usize,
-    ) -> Result<Self> {
+    ) -> JigsawResult<Self> {
         let origin_image = image::open(image_path)?;
         info!(
             "loaded image from {} with dimensions {}x{}",
@@ -596,14 +816,11 @@ impl JigsawGenerator {
             origin_image.width(),
             origin_image.height()
         );
-        Ok(JigsawGenerator {
-            origin_image: Arc::new(origin_image),
+        Ok(JigsawGenerator::new(
+            origin_image,
             pieces_in_column,
             pieces_in_row,
-            tab_size: None,
-            jitter: None,
-            seed: None,
-        })
+        ))
     }
 
     pub fn tab_size(mut self, tab_size: f32) -> Self {
@@ -616,11 +833,66 @@ impl JigsawGenerator {
         self
     }
 
+    pub fn warp(mut self, warp: f32) -> Self {
+        self.warp = Some(warp);
+        self
+    }
+
+    /// Sets the shape of the tabs, see [`EdgeProfile`]. Defaults to [`ClassicProfile`].
+    pub fn edge_profile(mut self, edge_profile: impl EdgeProfile + 'static) -> Self {
+        self.edge_profile = Arc::new(edge_profile);
+        self
+    }
+
     pub fn seed(mut self, seed: usize) -> Self {
         self.seed = Some(seed);
         self
     }
 
+    /// Sets the maximum width and height the image is scaled down to when `generate` is asked to
+    /// resize it. The aspect ratio is kept. Defaults to 1920x1200.
+    pub fn max_dimensions(mut self, width: u32, height: u32) -> Self {
+        self.max_dimensions = (width, height);
+        self
+    }
+
+    /// Scales the image to exactly the given width and height, whether `generate` is asked to
+    /// resize it or not.
+    pub fn target_size(mut self, width: u32, height: u32) -> Self {
+        self.target_size = Some((width, height));
+        self
+    }
+
+    /// Sets the filter to scale the image with. Defaults to [`FilterType::Lanczos3`].
+    pub fn resampling(mut self, filter: FilterType) -> Self {
+        self.resampling = filter;
+        self
+    }
+
+    /// Cuts the center of the image to the given ratio of width to height before it is scaled.
+    pub fn crop_to_aspect_ratio(mut self, aspect_ratio: f32) -> Self {
+        self.aspect_ratio = Some(aspect_ratio);
+        self
+    }
+
+    /// Treats the pixels with an alpha value up to the threshold as transparent background, for
+    /// images of cut-out shapes. Pieces without any other pixel are dropped, and the pieces along
+    /// the silhouette become border pieces. The remaining pieces are numbered again. By default
+    /// the whole image is content.
+    pub fn alpha_threshold(mut self, threshold: u8) -> Self {
+        self.alpha_threshold = Some(threshold);
+        self
+    }
+
+    /// Lets the puzzle wrap around: the edges on the right and bottom border of the image join
+    /// the pieces there with the ones on the left and top border, so there are no border pieces
+    /// at all. The template image is padded, see [`WrapAround`]. Only the [`GameMode::Classic`]
+    /// and [`GameMode::Square`] layouts can wrap around. Defaults to `false`.
+    pub fn wrap_around(mut self, wrap_around: bool) -> Self {
+        self.wrap_around = wrap_around;
+        self
+    }
+
     pub fn origin_image(&self) -> &DynamicImage {
         &self.origin_image
     }
@@ -637,12 +909,36 @@ impl JigsawGenerator {
         self.pieces_in_column * self.pieces_in_row
     }
 
-    pub fn generate(&self, game_mode: GameMode, resize: bool) -> Result<JigsawTemplate> {
-        let target_image = if resize {
-            Arc::new(scale_image(&self.origin_image))
-        } else {
-            self.origin_image.clone()
-        };
+    /// Generates the pieces for the given [`GameMode`]. The image is scaled down to the
+    /// [`JigsawGenerator::max_dimensions`] if `resize` is set, unless there is a
+    /// [`JigsawGenerator::target_size`].
+    ///
+    /// Returns an error if the tab size, jitter, warp or aspect ratio are out of range, if there
+    /// are no pieces in a column or a row, or if the pieces would be smaller than
+    /// [`MIN_PIECE_SIZE`].
+    pub fn generate(&self, game_mode: GameMode, resize: bool) -> JigsawResult<JigsawTemplate> {
+        let template = self.generate_layout(game_mode, resize)?;
+        match self.alpha_threshold {
+            Some(threshold) => Ok(template.follow_silhouette(threshold)),
+            None => Ok(template),
+        }
+    }
+
+    /// Generates the pieces for the whole image
+    fn generate_layout(&self, game_mode: GameMode, resize: bool) -> JigsawResult<JigsawTemplate> {
+        check_tab_size(self.tab_size)?;
+        check_jitter(self.jitter)?;
+        let warp = check_warp(self.warp)?;
+        if self.wrap_around && matches!(game_mode, GameMode::Hexagon | GameMode::Irregular) {
+            return Err(JigsawError::UnsupportedWrapAround(game_mode));
+        }
+        if self.pieces_in_column == 0 || self.pieces_in_row == 0 {
+            return Err(JigsawError::ZeroPieces {
+                pieces_in_column: self.pieces_in_column,
+                pieces_in_row: self.pieces_in_row,
+            });
+        }
+        let target_image = self.target_image(resize)?;
         let (target_image_width, target_image_height) = target_image.dimensions();
         info!(
             "start processing image with {}x{}",
@@ -652,52 +948,111 @@ impl JigsawGenerator {
         let image_height = target_image_height as f32;
         let pieces_in_column = self.pieces_in_column;
         let pieces_in_row = self.pieces_in_row;
+        if image_width / (pieces_in_column as f32) < MIN_PIECE_SIZE
+            || image_height / (pieces_in_row as f32) < MIN_PIECE_SIZE
+        {
+            return Err(JigsawError::ImageTooSmall {
+                width: target_image_width,
+                height: target_image_height,
+                pieces_in_column,
+                pieces_in_row,
+            });
+        }
         let (starting_points_x, piece_width) = divide_axis(image_width, pieces_in_column);
         let (starting_points_y, piece_height) = divide_axis(image_height, pieces_in_row);
 
-        let (vertical_edges, horizontal_edges) = match game_mode {
-            GameMode::Classic => self.classic_generator(
-                image_width,
-                image_height,
-                &starting_points_x,
-                piece_width,
-                &starting_points_y,
-                piece_height,
-            ),
-            GameMode::Square => self.square_generator(
-                image_width,
-                image_height,
-                &starting_points_x,
-                piece_width,
-                &starting_points_y,
-                piece_height,
-            ),
+        let mut corners = self.corner_lattice(
+            warp / 100.0,
+            image_width,
+            image_height,
+            &starting_points_x,
+            piece_width,
+            &starting_points_y,
+            piece_height,
+        );
+
+        let (mut vertical_edges, mut horizontal_edges) = match game_mode {
+            GameMode::Classic => self.classic_generator(&corners, piece_width, piece_height)?,
+            GameMode::Square => self.square_generator(&corners),
+            GameMode::Hexagon => {
+                let pieces =
+                    self.hexagon_generator(target_image.dimensions(), piece_width, piece_height)?;
+                return Ok(JigsawTemplate::new(
+                    self.untangle(
+                        pieces,
+                        game_mode,
+                        target_image.dimensions(),
+                        piece_width,
+                        piece_height,
+                        None,
+                    )?,
+                    target_image,
+                    (piece_width, piece_height),
+                    (pieces_in_column, pieces_in_row),
+                    None,
+                ));
+            }
+            GameMode::Irregular => {
+                let pieces =
+                    self.voronoi_generator(target_image.dimensions(), piece_width, piece_height)?;
+                return Ok(JigsawTemplate::new(
+                    self.untangle(
+                        pieces,
+                        game_mode,
+                        target_image.dimensions(),
+                        piece_width,
+                        piece_height,
+                        None,
+                    )?,
+                    target_image,
+                    (piece_width, piece_height),
+                    (pieces_in_column, pieces_in_row),
+                    None,
+                ));
+            }
+        };
+
+        // the tabs on the border reach beyond the image, which is padded with its opposite side
+        let wrap_around = self.wrap_around.then(|| {
+            wrap::pad_layout(
+                target_image.dimensions(),
+                &mut corners,
+                &mut vertical_edges,
+                &mut horizontal_edges,
+            )
+        });
+        let image_size = match &wrap_around {
+            Some(wrap_around) => wrap_around.image_size(),
+            None => target_image.dimensions(),
         };
 
         let mut pieces = vec![];
         let mut i = 0;
-        for y in starting_points_y.iter() {
-            for x in starting_points_x.iter() {
+        for row_corners in corners.iter().take(pieces_in_row) {
+            for corner in row_corners.iter().take(pieces_in_column) {
                 let (top_index, right_index, bottom_index, left_index) =
                     get_border_indices(i, pieces_in_column);
 
                 // debug!("starting process piece {i} {top_index} {right_index} {bottom_index} {left_index}");
                 debug!("starting process piece {i}");
 
-                let is_boarder = i < pieces_in_column
-                    || i >= (pieces_in_column * (pieces_in_row - 1))
-                    || i % pieces_in_column == 0
-                    || i % pieces_in_column == (pieces_in_column - 1);
+                let is_boarder = wrap_around.is_none()
+                    && (i < pieces_in_column
+                        || i >= (pieces_in_column * (pieces_in_row - 1))
+                        || i % pieces_in_column == 0
+                        || i % pieces_in_column == (pieces_in_column - 1));
 
                 let piece = JigsawPiece::new(
                     i,
-                    (*x, *y),
-                    target_image.dimensions(),
+                    *corner,
+                    image_size,
                     (piece_width, piece_height),
-                    horizontal_edges[top_index].clone(),
-                    vertical_edges[right_index].clone(),
-                    horizontal_edges[bottom_index].clone(),
-                    vertical_edges[left_index].clone(),
+                    vec![
+                        horizontal_edges[top_index].clone(),
+                        vertical_edges[right_index].clone(),
+                        horizontal_edges[bottom_index].clone(),
+                        vertical_edges[left_index].clone(),
+                    ],
                     is_boarder,
                 )?;
 
@@ -709,82 +1064,185 @@ impl JigsawGenerator {
             }
         }
 
-        Ok(JigsawTemplate {
+        let pieces = self.untangle(
             pieces,
-            origin_image: target_image,
-            piece_dimensions: (piece_width, piece_height),
-            number_of_pieces: (pieces_in_column, pieces_in_row),
-        })
+            game_mode,
+            image_size,
+            piece_width,
+            piece_height,
+            wrap_around.as_ref(),
+        )?;
+        let (pieces, target_image, wrap_around) = match wrap_around {
+            Some(wrap_around) => {
+                // replaced tabs may reach beyond the margin
+                let (pieces, wrap_around) = wrap::fit_margin(pieces, wrap_around)?;
+                let image = wrap_around.pad_image(&target_image);
+                (pieces, Arc::new(image), Some(wrap_around))
+            }
+            None => (pieces, target_image, None),
+        };
+        Ok(JigsawTemplate::new(
+            pieces,
+            target_image,
+            (piece_width, piece_height),
+            (pieces_in_column, pieces_in_row),
+            wrap_around,
+        ))
+    }
+
+    /// Returns the image to cut the pieces from, after cropping and scaling the original image
+    fn target_image(&self, resize: bool) -> JigsawResult<Arc<DynamicImage>> {
+        let mut image = self.origin_image.clone();
+        if let Some(aspect_ratio) = self.aspect_ratio {
+            if !(aspect_ratio.is_finite() && aspect_ratio > 0.0) {
+                return Err(JigsawError::InvalidAspectRatio(aspect_ratio));
+            }
+            let (width, height) = image.dimensions();
+            let (crop_width, crop_height) = if width as f32 / height as f32 > aspect_ratio {
+                let crop_width = (height as f32 * aspect_ratio).round().max(1.0) as u32;
+                (crop_width.min(width), height)
+            } else {
+                let crop_height = (width as f32 / aspect_ratio).round().max(1.0) as u32;
+                (width, crop_height.min(height))
+            };
+            image = Arc::new(image.crop_imm(
+                (width - crop_width) / 2,
+                (height - crop_height) / 2,
+                crop_width,
+                crop_height,
+            ));
+        }
+        if let Some((width, height)) = self.target_size {
+            if image.dimensions() != (width, height) {
+                image = Arc::new(image.resize_exact(width, height, self.resampling));
+            }
+        } else if resize {
+            image = Arc::new(scale_image(&image, self.max_dimensions, self.resampling));
+        }
+        Ok(image)
+    }
+
+    /// Returns a new [`EdgeContourGenerator`] with the tab settings of this generator
+    fn contour_generator(
+        &self,
+        piece_width: f32,
+        piece_height: f32,
+    ) -> JigsawResult<EdgeContourGenerator> {
+        Ok(EdgeContourGenerator::new(
+            piece_width,
+            piece_height,
+            self.tab_size,
+            self.jitter,
+            self.seed,
+        )?
+        .profile(self.edge_profile.clone()))
+    }
+
+    /// Returns a source of statistically evenly distributed values between a `min` and a `max`
+    /// value for the layout of the pieces. It is derived from the seed like the contours, but
+    /// yields an independent sequence.
+    fn layout_random(&self) -> impl FnMut(f32, f32) -> f32 {
+        let mut counter = EdgeContourGenerator::mix(self.seed.unwrap_or(0) as u64).rotate_left(32);
+        move |min, max| {
+            counter = counter.wrapping_add(1);
+            EdgeContourGenerator::uniform(min, max, counter)
+        }
     }
 
-    fn square_generator(
+    /// Returns the corners of the pieces row by row, including the corners on the right and
+    /// bottom image border. Without a warp factor the corners lie exactly on the lattice of the
+    /// given starting points. If the puzzle wraps around, the corners are rounded to a grid, and
+    /// the corners on the right and bottom border are the ones on the left and top border moved
+    /// by the size of the image.
+    #[allow(clippy::too_many_arguments)]
+    fn corner_lattice(
         &self,
+        warp: f32,
         image_width: f32,
         image_height: f32,
         starting_points_x: &[f32],
-        _piece_width: f32,
+        piece_width: f32,
         starting_points_y: &[f32],
-        _piece_height: f32,
-    ) -> (Vec<Edge>, Vec<Edge>) {
+        piece_height: f32,
+    ) -> Vec<Vec<(f32, f32)>> {
+        let mut random = self.layout_random();
+        let xs: Vec<f32> = starting_points_x
+            .iter()
+            .copied()
+            .chain([image_width])
+            .collect();
+        let ys: Vec<f32> = starting_points_y
+            .iter()
+            .copied()
+            .chain([image_height])
+            .collect();
+        let mut shift = |inner: bool, length: f32| {
+            if inner && warp > 0.0 {
+                random(-warp, warp) * length
+            } else {
+                0.0
+            }
+        };
+        let mut corners = vec![];
+        for (index_y, y) in ys.iter().enumerate() {
+            let inner_y = index_y > 0 && index_y < ys.len() - 1;
+            let mut row = vec![];
+            for (index_x, x) in xs.iter().enumerate() {
+                let inner_x = index_x > 0 && index_x < xs.len() - 1;
+                let shift_x = shift(inner_x, piece_width);
+                let shift_y = shift(inner_y, piece_height);
+                row.push((round(x + shift_x), round(y + shift_y)));
+            }
+            corners.push(row);
+        }
+        if self.wrap_around {
+            for corner in corners.iter_mut().flatten() {
+                *corner = (wrap::snap_to_grid(corner.0), wrap::snap_to_grid(corner.1));
+            }
+            for row in corners.iter_mut() {
+                let (x, y) = row[0];
+                *row.last_mut().expect("a corner on the right border") = (x + image_width, y);
+            }
+            let top = corners[0].clone();
+            let bottom = corners
+                .last_mut()
+                .expect("a row of corners on the bottom border");
+            for (corner, (x, y)) in bottom.iter_mut().zip(top) {
+                *corner = (x, y + image_height);
+            }
+        }
+        corners
+    }
+
+    fn square_generator(&self, corners: &[Vec<(f32, f32)>]) -> (Vec<Edge>, Vec<Edge>) {
+        let rows = corners.len() - 1;
+        let columns = corners[0].len() - 1;
         let mut vertical_edges = vec![];
         let mut horizontal_edges = vec![];
-        let mut top_border = true;
-        for index_y in 0..starting_points_y.len() {
-            let mut left_border = true;
-            for index_x in 0..starting_points_x.len() {
-                horizontal_edges.push(if top_border {
-                    Edge::StraightEdge(StraightEdge {
-                        starting_point: (starting_points_x[index_x], 0.0),
-                        end_point: (end_point_pos(index_x, starting_points_x, image_width), 0.0),
-                    })
-                } else {
-                    Edge::StraightEdge(StraightEdge {
-                        starting_point: (starting_points_x[index_x], starting_points_y[index_y]),
-                        end_point: (
-                            end_point_pos(index_x, starting_points_x, image_width),
-                            starting_points_y[index_y],
-                        ),
-                    })
-                });
-
-                vertical_edges.push(if left_border {
-                    Edge::StraightEdge(StraightEdge {
-                        starting_point: (0.0, starting_points_y[index_y]),
-                        end_point: (0.0, end_point_pos(index_y, starting_points_y, image_height)),
-                    })
-                } else {
-                    Edge::StraightEdge(StraightEdge {
-                        starting_point: (starting_points_x[index_x], starting_points_y[index_y]),
-                        end_point: (
-                            starting_points_x[index_x],
-                            end_point_pos(index_y, starting_points_y, image_height),
-                        ),
-                    })
-                });
-
-                left_border = false;
+        for index_y in 0..rows {
+            for index_x in 0..columns {
+                horizontal_edges.push(Edge::StraightEdge(StraightEdge {
+                    starting_point: corners[index_y][index_x],
+                    end_point: corners[index_y][index_x + 1],
+                }));
+                vertical_edges.push(Edge::StraightEdge(StraightEdge {
+                    starting_point: corners[index_y][index_x],
+                    end_point: corners[index_y + 1][index_x],
+                }));
             }
 
-            top_border = false;
-
             // Draw right outer edge
             vertical_edges.push(Edge::StraightEdge(StraightEdge {
-                starting_point: (image_width, starting_points_y[index_y]),
-                end_point: (
-                    image_width,
-                    end_point_pos(index_y, starting_points_y, image_height),
-                ),
+                starting_point: corners[index_y][columns],
+                end_point: corners[index_y + 1][columns],
             }));
         }
 
         // Draw bottom outer edges
-        for index_x in 0..starting_points_x.len() {
+        for index_x in 0..columns {
             horizontal_edges.push(Edge::StraightEdge(StraightEdge {
-                starting_point: (starting_points_x[index_x], image_height),
-                end_point: (
-                    end_point_pos(index_x, starting_points_x, image_width),
-                    image_height,
-                ),
+                starting_point: corners[rows][index_x],
+                end_point: corners[rows][index_x + 1],
             }))
         }
 
@@ -793,130 +1251,357 @@ impl JigsawGenerator {
 
     fn classic_generator(
         &self,
-        image_width: f32,
-        image_height: f32,
-        starting_points_x: &[f32],
+        corners: &[Vec<(f32, f32)>],
         piece_width: f32,
-        starting_points_y: &[f32],
         piece_height: f32,
-    ) -> (Vec<Edge>, Vec<Edge>) {
-        let mut contour_gen = EdgeContourGenerator::new(
-            piece_width,
-            piece_height,
-            self.tab_size,
-            self.jitter,
-            self.seed,
-        );
+    ) -> JigsawResult<(Vec<Edge>, Vec<Edge>)> {
+        let mut contour_gen = self.contour_generator(piece_width, piece_height)?;
+        let mut indented_edge = |starting_point: (f32, f32), end_point: (f32, f32)| {
+            let edge =
+                Edge::IndentedEdge(lattice_edge(starting_point, end_point, &mut contour_gen)?);
+            // the copies of the edges on the right and bottom border have to be exact
+            Ok::<_, JigsawError>(if self.wrap_around {
+                edge.snapped()
+            } else {
+                edge
+            })
+        };
+        let rows = corners.len() - 1;
+        let columns = corners[0].len() - 1;
         let mut vertical_edges = vec![];
         let mut horizontal_edges = vec![];
-        let mut top_border = true;
-        for index_y in 0..starting_points_y.len() {
-            let mut left_border = true;
-            for index_x in 0..starting_points_x.len() {
-                horizontal_edges.push(if top_border {
+        for index_y in 0..rows {
+            for index_x in 0..columns {
+                let starting_point = corners[index_y][index_x];
+                horizontal_edges.push(if index_y == 0 && !self.wrap_around {
                     Edge::StraightEdge(StraightEdge {
-                        starting_point: (starting_points_x[index_x], 0.0),
-                        end_point: (end_point_pos(index_x, starting_points_x, image_width), 0.0),
+                        starting_point,
+                        end_point: corners[index_y][index_x + 1],
                     })
                 } else {
-                    Edge::IndentedEdge(IndentedEdge::new(
-                        (starting_points_x[index_x], starting_points_y[index_y]),
-                        (
-                            end_point_pos(index_x, starting_points_x, image_width),
-                            starting_points_y[index_y],
-                        ),
-                        &mut contour_gen,
-                    ))
+                    indented_edge(starting_point, corners[index_y][index_x + 1])?
                 });
-                vertical_edges.push(if left_border {
+                vertical_edges.push(if index_x == 0 && !self.wrap_around {
                     Edge::StraightEdge(StraightEdge {
-                        starting_point: (0.0, starting_points_y[index_y]),
-                        end_point: (0.0, end_point_pos(index_y, starting_points_y, image_height)),
+                        starting_point,
+                        end_point: corners[index_y + 1][index_x],
                     })
                 } else {
-                    Edge::IndentedEdge(IndentedEdge::new(
-                        (starting_points_x[index_x], starting_points_y[index_y]),
-                        (
-                            starting_points_x[index_x],
-                            end_point_pos(index_y, starting_points_y, image_height),
-                        ),
-                        &mut contour_gen,
-                    ))
+                    indented_edge(starting_point, corners[index_y + 1][index_x])?
                 });
-                left_border = false;
             }
-            top_border = false;
             // Draw right outer edge
-            vertical_edges.push(Edge::StraightEdge(StraightEdge {
-                starting_point: (image_width, starting_points_y[index_y]),
-                end_point: (
-                    image_width,
-                    end_point_pos(index_y, starting_points_y, image_height),
-                ),
-            }));
+            vertical_edges.push(if self.wrap_around {
+                // the left edge of the row, moved to the right border
+                let left_edge = &vertical_edges[vertical_edges.len() - columns];
+                left_edge.translated(corners[index_y][columns].0 - corners[index_y][0].0, 0.0)
+            } else {
+                Edge::StraightEdge(StraightEdge {
+                    starting_point: corners[index_y][columns],
+                    end_point: corners[index_y + 1][columns],
+                })
+            });
         }
 
         // Draw bottom outer edges
-        for index_x in 0..starting_points_x.len() {
-            horizontal_edges.push(Edge::StraightEdge(StraightEdge {
-                starting_point: (starting_points_x[index_x], image_height),
-                end_point: (
-                    end_point_pos(index_x, starting_points_x, image_width),
-                    image_height,
-                ),
-            }))
+        for index_x in 0..columns {
+            horizontal_edges.push(if self.wrap_around {
+                // the top edge of the column, moved to the bottom border
+                horizontal_edges[index_x].translated(0.0, corners[rows][0].1 - corners[0][0].1)
+            } else {
+                Edge::StraightEdge(StraightEdge {
+                    starting_point: corners[rows][index_x],
+                    end_point: corners[rows][index_x + 1],
+                })
+            })
         }
-        (vertical_edges, horizontal_edges)
+        Ok((vertical_edges, horizontal_edges))
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(from = "JigsawTemplateData")
+)]
 pub struct JigsawTemplate {
     /// The generated jigsaw puzzle pieces
     pub pieces: Vec<JigsawPiece>,
-    /// The original image from which the jigsaw puzzle pieces will be generated.
+    /// The original image from which the jigsaw puzzle pieces will be generated. It is not
+    /// serialised and is left empty when a template is deserialised.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub origin_image: Arc<DynamicImage>,
     /// The dimensions (width, length) in pixel
     pub piece_dimensions: (f32, f32),
     /// The number of pieces in the x- and the y-axis
     pub number_of_pieces: (usize, usize),
+    /// The neighbours of the pieces. For every edge of a piece, in the order of
+    /// [`JigsawPiece::edges`], the index of the piece on the other side, or `None` on the border
+    /// of the puzzle. It is rebuilt from the edges when a template is deserialised.
+    pub adjacency: Vec<Vec<Option<usize>>>,
+    /// Where the puzzle lies in the image if it wraps around, see
+    /// [`JigsawGenerator::wrap_around`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wrap_around: Option<WrapAround>,
+    /// The alpha value up to which pixels of the image are transparent background if the pieces
+    /// follow the silhouette of the image, see [`JigsawGenerator::alpha_threshold`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub alpha_threshold: Option<u8>,
 }
 
-/// Scales the given image to fit within the maximum width and height constraints.
-/// If the image dimensions exceed the maximum allowed dimensions, it scales the image down
-/// while maintaining the aspect ratio. Otherwise, it returns the original image.
-///
-/// # Arguments
-///
-/// * `image` - A reference to the `DynamicImage` that needs to be scaled.
-///
-/// # Returns
-///
-/// * `RgbaImage` - The scaled image as an `RgbaImage`.
-fn scale_image(image: &DynamicImage) -> DynamicImage {
-    let (width, height) = image.dimensions();
-    let scale = if width > MAX_WIDTH || height > MAX_HEIGHT {
-        let scale_x = MAX_WIDTH as f32 / width as f32;
-        let scale_y = MAX_HEIGHT as f32 / height as f32;
-        scale_x.min(scale_y)
-    } else {
-        1.0
-    };
-    if scale < 1.0 {
-        image.resize(
-            (width as f32 * scale) as u32,
-            (height as f32 * scale) as u32,
-            image::imageops::FilterType::Lanczos3,
+/// The serialised form of a [`JigsawTemplate`]. The adjacency of older or hand-written templates
+/// may be missing or stale, so it is rebuilt from the edges.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct JigsawTemplateData {
+    pieces: Vec<JigsawPiece>,
+    piece_dimensions: (f32, f32),
+    number_of_pieces: (usize, usize),
+    #[serde(default)]
+    wrap_around: Option<WrapAround>,
+    #[serde(default)]
+    alpha_threshold: Option<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl From<JigsawTemplateData> for JigsawTemplate {
+    fn from(data: JigsawTemplateData) -> Self {
+        let mut template = JigsawTemplate::new(
+            data.pieces,
+            Arc::default(),
+            data.piece_dimensions,
+            data.number_of_pieces,
+            data.wrap_around,
+        );
+        template.alpha_threshold = data.alpha_threshold;
+        template
+    }
+}
+
+impl JigsawTemplate {
+    /// Creates a new template and finds the neighbours of its pieces. The index of every piece
+    /// has to be its position in `pieces`.
+    pub(crate) fn new(
+        pieces: Vec<JigsawPiece>,
+        origin_image: Arc<DynamicImage>,
+        piece_dimensions: (f32, f32),
+        number_of_pieces: (usize, usize),
+        wrap_around: Option<WrapAround>,
+    ) -> Self {
+        let edge_key = |edge: &Edge| shared_edge_key(edge, wrap_around.as_ref());
+        let mut owners: HashMap<_, Vec<usize>> = HashMap::new();
+        for piece in pieces.iter() {
+            for edge in piece.edges.iter() {
+                owners.entry(edge_key(edge)).or_default().push(piece.index);
+            }
+        }
+        let adjacency = pieces
+            .iter()
+            .map(|piece| {
+                piece
+                    .edges
+                    .iter()
+                    .map(|edge| {
+                        owners[&edge_key(edge)]
+                            .iter()
+                            .copied()
+                            .find(|&other| other != piece.index)
+                    })
+                    .collect()
+            })
+            .collect();
+        JigsawTemplate {
+            pieces,
+            origin_image,
+            piece_dimensions,
+            number_of_pieces,
+            adjacency,
+            alpha_threshold: None,
+            wrap_around,
+        }
+    }
+
+    /// Returns the neighbours of a rectangular piece on its top, right, bottom and left side, in
+    /// this order, together with the side they are on. Pieces with more or less than four edges
+    /// have no sides, see [`JigsawTemplate::adjacent`] for them.
+    pub fn neighbors(&self, index: usize) -> [Option<(Side, usize)>; 4] {
+        let mut neighbors = [None; 4];
+        if let [top, right, bottom, left] = self.adjacency[index][..] {
+            let sides = [Side::Top, Side::Right, Side::Bottom, Side::Left];
+            for ((neighbor, side), slot) in [top, right, bottom, left]
+                .into_iter()
+                .zip(sides)
+                .zip(neighbors.iter_mut())
+            {
+                *slot = neighbor.map(|neighbor| (side, neighbor));
+            }
+        }
+        neighbors
+    }
+
+    /// Returns the indices of all pieces which share an edge with the given piece
+    pub fn adjacent(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.adjacency[index].iter().flatten().copied()
+    }
+
+    /// Returns the offsets of the location of a piece from the location of its neighbour `other`
+    /// at which both are joined, in world coordinates with the y-axis pointing up. There is one
+    /// offset for every edge they share, the pieces of a puzzle which wraps around may join on
+    /// opposite borders of the image as well.
+    pub fn join_offsets(&self, index: usize, other: usize) -> Vec<(f32, f32)> {
+        let (piece, other_piece) = (&self.pieces[index], &self.pieces[other]);
+        let edge_key = |edge: &Edge| shared_edge_key(edge, self.wrap_around.as_ref());
+        piece
+            .edges
+            .iter()
+            .zip(self.adjacency[index].iter())
+            .filter(|(_, neighbor)| **neighbor == Some(other))
+            .filter_map(|(edge, _)| {
+                let key = edge_key(edge);
+                let twin = other_piece
+                    .edges
+                    .iter()
+                    .find(|twin| edge_key(twin) == key)?;
+                // the copies of an edge on opposite borders lie apart by the size of the image
+                let (x, y) = edge.starting_point();
+                let (twin_x, twin_y) = twin.starting_point();
+                let (dx, dy) = (twin_x - x, twin_y - y);
+                Some((
+                    piece.start_point.0 + dx - other_piece.start_point.0,
+                    other_piece.start_point.1 - (piece.start_point.1 + dy),
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the offset of the location of a piece from the location of its neighbour `other`
+    /// if the given locations of both pieces (in world coordinates) are close enough to one of
+    /// their joined positions to snap them together, see [`JigsawTemplate::join_offsets`]
+    pub fn snap_offset(
+        &self,
+        index: usize,
+        other: usize,
+        index_loc: (f32, f32),
+        other_loc: (f32, f32),
+    ) -> Option<(f32, f32)> {
+        self.join_offsets(index, other)
+            .into_iter()
+            .find(|(offset_x, offset_y)| {
+                (index_loc.0 - other_loc.0 - offset_x).abs() < COMPARE_THRESHOLD
+                    && (index_loc.1 - other_loc.1 - offset_y).abs() < COMPARE_THRESHOLD
+            })
+    }
+
+    /// Returns the width and height of the image the template was generated for. Falls back to
+    /// the extent of the piece edges if the template has no image, e.g. after deserialisation.
+    pub fn image_dimensions(&self) -> (u32, u32) {
+        let (width, height) = self.origin_image.dimensions();
+        if width > 0 && height > 0 {
+            return (width, height);
+        }
+        let (width, height) = self
+            .pieces
+            .iter()
+            .flat_map(|piece| piece.edges.iter())
+            .flat_map(|edge| [edge.starting_point(), edge.end_point()])
+            .fold((0.0f32, 0.0f32), |(w, h), (x, y)| (w.max(x), h.max(y)));
+        (width.round() as u32, height.round() as u32)
+    }
+}
+
+/// Scales the given image to fit within the maximum width and height constraints.
+/// If the image dimensions exceed the maximum allowed dimensions, it scales the image down
+/// while maintaining the aspect ratio. Otherwise, it returns the original image.
+///
+/// # Arguments
+///
+/// * `image` - A reference to the `DynamicImage` that needs to be scaled.
+/// * `max_dimensions` - The maximum width and height of the scaled image.
+/// * `filter` - The resampling filter used for scaling.
+///
+/// # Returns
+///
+/// * `DynamicImage` - The scaled image.
+fn scale_image(
+    image: &DynamicImage,
+    max_dimensions: (u32, u32),
+    filter: FilterType,
+) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    let (max_width, max_height) = max_dimensions;
+    let scale = if width > max_width || height > max_height {
+        let scale_x = max_width as f32 / width as f32;
+        let scale_y = max_height as f32 / height as f32;
+        scale_x.min(scale_y)
+    } else {
+        1.0
+    };
+    if scale < 1.0 {
+        image.resize(
+            (width as f32 * scale) as u32,
+            (height as f32 * scale) as u32,
+            filter,
         )
     } else {
         image.clone()
     }
 }
 
+/// Options for [`JigsawPiece::crop_with_options`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CropOptions {
+    /// Number of samples per pixel along each axis for the pixels on the piece's contour. `1`
+    /// decides per pixel whether it's inside the piece, which yields jagged edges. Higher values
+    /// compute the alpha from the covered share of the pixel for smooth edges. Defaults to `4`.
+    pub supersampling: u32,
+    /// Factor the image of the piece is scaled by, see [`JigsawPiece::crop_size`]. The image is
+    /// resampled, but the contour is rasterised at the scaled resolution, so it stays sharp when
+    /// the piece is enlarged. It has to be a positive number. Defaults to `1.0`.
+    pub scale: f32,
+}
+
+impl Default for CropOptions {
+    fn default() -> Self {
+        CropOptions {
+            supersampling: 4,
+            scale: 1.0,
+        }
+    }
+}
+
+impl CropOptions {
+    pub fn supersampling(mut self, supersampling: u32) -> Self {
+        self.supersampling = supersampling;
+        self
+    }
+
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Returns an error if the scale is not a positive number
+    pub(crate) fn validate(&self) -> JigsawResult<()> {
+        if self.scale.is_finite() && self.scale > 0.0 {
+            Ok(())
+        } else {
+            Err(JigsawError::InvalidScale(self.scale))
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(from = "JigsawPieceData")
+)]
 pub struct JigsawPiece {
     pub index: usize,
     pub start_point: (f32, f32),
+    /// The closed contour of the piece. It is not serialised but rebuilt from the edges.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
     pub subpath: Subpath<PuzzleId>,
     pub width: f32,
     pub height: f32,
@@ -924,45 +1609,36 @@ pub struct JigsawPiece {
     pub top_left_y: u32,
     pub crop_width: u32,
     pub crop_height: u32,
-    pub top_edge: Edge,
-    pub right_edge: Edge,
-    pub bottom_edge: Edge,
-    pub left_edge: Edge,
+    /// The edges of the piece in clockwise order. Rectangular pieces have their top, right,
+    /// bottom and left edge in this order, see [`Side`]. Neighbouring pieces share equal edges.
+    pub edges: Vec<Edge>,
     pub is_boarder: bool,
 }
 
 impl JigsawPiece {
-    #[allow(clippy::too_many_arguments)]
+    /// Creates a new piece from its edges in clockwise order. Every edge has to start or end where
+    /// the previous one ends, its own direction doesn't matter.
     pub fn new(
         index: usize,
         start_point: (f32, f32),
         origin_image_size: (u32, u32),
         piece_size: (f32, f32),
-        top_edge: Edge,
-        right_edge: Edge,
-        bottom_edge: Edge,
-        left_edge: Edge,
+        edges: Vec<Edge>,
         is_boarder: bool,
-    ) -> Result<Self> {
-        let top_beziers = top_edge.to_beziers(false);
-        let right_beziers = right_edge.to_beziers(false);
-        let bottom_beziers = bottom_edge.to_beziers(true);
-        let left_beziers = left_edge.to_beziers(true);
-        let beziers: Vec<_> = vec![top_beziers, right_beziers, bottom_beziers, left_beziers]
-            .into_iter()
-            .flatten()
-            .collect();
-        let subpath: Subpath<PuzzleId> = Subpath::from_beziers(&beziers, true);
+    ) -> JigsawResult<Self> {
+        let subpath = contour(&edges);
         let [box_min, box_max] = subpath
             .bounding_box()
-            .ok_or(anyhow!("No bounding box found"))?;
+            .ok_or(JigsawError::EmptyBoundingBox(index))?;
 
         let (image_width, image_height) = (origin_image_size.0, origin_image_size.1);
         let (piece_width, piece_height) = (piece_size.0, piece_size.1);
         let top_left_x = (box_min.x as f32).max(0.0) as u32;
         let top_left_y = (box_min.y as f32).max(0.0) as u32;
-        let mut crop_width = (box_max.x as f32 - box_min.x as f32).max(piece_width) as u32;
-        let mut crop_height = (box_max.y as f32 - box_min.y as f32).max(piece_height) as u32;
+        // round the far side up, the pixels it passes through belong to the piece as well
+        let mut crop_width = (box_max.x.ceil() as f32 - top_left_x as f32).max(piece_width) as u32;
+        let mut crop_height =
+            (box_max.y.ceil() as f32 - top_left_y as f32).max(piece_height) as u32;
         if top_left_x + crop_width > image_width {
             crop_width = image_width - top_left_x;
         }
@@ -980,21 +1656,66 @@ impl JigsawPiece {
             top_left_y,
             crop_width,
             crop_height,
-            top_edge,
-            right_edge,
-            bottom_edge,
-            left_edge,
+            edges,
             is_boarder,
         })
     }
 
+    /// Returns the edge on the given side of a rectangular piece, or `None` if the piece isn't
+    /// rectangular
+    pub fn edge(&self, side: Side) -> Option<&Edge> {
+        if self.edges.len() == 4 {
+            self.edges.get(side as usize)
+        } else {
+            None
+        }
+    }
+
     pub fn calc_offset(&self) -> (f32, f32) {
         let x = self.start_point.0 - self.top_left_x as f32;
         let y = self.start_point.1 - self.top_left_y as f32;
         (x, y)
     }
 
+    /// Crops the piece from the image. Every pixel is either fully inside or outside the piece.
     pub fn crop(&self, image: &DynamicImage) -> DynamicImage {
+        self.render_crop(image, &CropOptions::default().supersampling(1))
+    }
+
+    /// Crops the piece from the image and scales it by `factor`, with an anti-aliased alpha mask
+    /// along the contour. Crops of several factors cover the same area, so they can replace each
+    /// other when drawn at the same size.
+    ///
+    /// Returns an error if the factor is not a positive number.
+    pub fn crop_scaled(&self, image: &DynamicImage, factor: f32) -> JigsawResult<DynamicImage> {
+        self.crop_with_options(image, &CropOptions::default().scale(factor))
+    }
+
+    /// Returns the size of the image of the piece scaled by `factor`. The far side is rounded up
+    /// like the one of the crop rectangle.
+    pub fn crop_size(&self, factor: f32) -> (u32, u32) {
+        (
+            ((self.crop_width as f32 * factor).ceil() as u32).max(1),
+            ((self.crop_height as f32 * factor).ceil() as u32).max(1),
+        )
+    }
+
+    /// Crops the piece from the image like [`JigsawPiece::crop`], but with an anti-aliased alpha
+    /// mask along the contour if [`CropOptions::supersampling`] is greater than `1`, and scaled by
+    /// [`CropOptions::scale`]
+    ///
+    /// Returns an error if the scale is not a positive number.
+    pub fn crop_with_options(
+        &self,
+        image: &DynamicImage,
+        options: &CropOptions,
+    ) -> JigsawResult<DynamicImage> {
+        options.validate()?;
+        Ok(self.render_crop(image, options))
+    }
+
+    /// Crops the piece with options which are known to be valid
+    fn render_crop(&self, image: &DynamicImage, options: &CropOptions) -> DynamicImage {
         trace!("start crop piece {} image", self.index);
         let mut piece_image = image
             .view(
@@ -1004,47 +1725,68 @@ impl JigsawPiece {
                 self.crop_height,
             )
             .to_image();
+        let scale = options.scale as f64;
+        if options.scale != 1.0 {
+            let (width, height) = self.crop_size(options.scale);
+            piece_image =
+                image::imageops::resize(&piece_image, width, height, FilterType::Triangle);
+        }
 
-        piece_image
-            .par_enumerate_pixels_mut()
-            .for_each(|(x, y, pixel)| {
-                let point = DVec2::new(
-                    self.top_left_x as f64 + x as f64,
-                    self.top_left_y as f64 + y as f64,
-                );
-                if !self.contains(point) {
-                    *pixel = Rgba([0, 0, 0, 0])
-                }
-            });
+        let polygon: Vec<DVec2> = raster::flatten(&self.subpath)
+            .into_iter()
+            .map(|point| point * scale)
+            .collect();
+        let coverage = raster::coverage(
+            &polygon,
+            DVec2::new(self.top_left_x as f64, self.top_left_y as f64) * scale,
+            piece_image.width(),
+            piece_image.height(),
+            options.supersampling,
+        );
+        let width = piece_image.width();
+        #[cfg(feature = "parallel")]
+        let pixels = piece_image.par_enumerate_pixels_mut();
+        #[cfg(not(feature = "parallel"))]
+        let pixels = piece_image.enumerate_pixels_mut();
+        pixels.for_each(|(x, y, pixel)| {
+            let coverage = coverage[(y * width + x) as usize];
+            if coverage == 0.0 {
+                *pixel = Rgba([0, 0, 0, 0]);
+            } else {
+                pixel.0[3] = (pixel.0[3] as f32 * coverage).round() as u8;
+            }
+        });
 
-        self.draw_bezier(&mut piece_image, WHITE_COLOR);
+        self.draw_bezier(&mut piece_image, WHITE_COLOR, scale);
 
         piece_image.into()
     }
 
-    /// Fills the not transparent parts of the image with white color
+    /// Fills the not transparent parts of the image with white color, keeping their alpha value
     pub fn fill_white(&self, image: &DynamicImage) -> DynamicImage {
         let mut white_image = image.to_rgba8();
-        white_image
-            .par_enumerate_pixels_mut()
-            .for_each(|(_, _, pixel)| {
-                if pixel.0[3] != 0 {
-                    *pixel = WHITE_COLOR;
-                }
-            });
+        #[cfg(feature = "parallel")]
+        let pixels = white_image.par_enumerate_pixels_mut();
+        #[cfg(not(feature = "parallel"))]
+        let pixels = white_image.enumerate_pixels_mut();
+        pixels.for_each(|(_, _, pixel)| {
+            if pixel.0[3] != 0 {
+                *pixel = Rgba([255, 255, 255, pixel.0[3]]);
+            }
+        });
 
         white_image.into()
     }
 
-    fn draw_bezier(&self, image: &mut RgbaImage, color: Rgba<u8>) {
-        let top_left_x = self.top_left_x as f64;
-        let top_left_y = self.top_left_y as f64;
-        let top_left = DVec2::new(top_left_x, top_left_y);
+    /// Draws the contour of the piece into its crop scaled by `scale`
+    fn draw_bezier(&self, image: &mut RgbaImage, color: Rgba<u8>, scale: f64) {
+        let top_left = DVec2::new(self.top_left_x as f64, self.top_left_y as f64);
+        let local = |point: DVec2| (point - top_left) * scale;
         for path in self.subpath.iter() {
             match path.handles {
                 BezierHandles::Linear => {
-                    let start = path.start - top_left - 1.0;
-                    let end = path.end - top_left - 1.0;
+                    let start = local(path.start) - 1.0;
+                    let end = local(path.end) - 1.0;
 
                     imageproc::drawing::draw_line_segment_mut(
                         image,
@@ -1058,17 +1800,17 @@ impl JigsawPiece {
                     handle_start,
                     handle_end,
                 } => {
-                    let start = (path.start.x - top_left_x, path.start.y - top_left_y);
-                    let end = (path.end.x - top_left_x, path.end.y - top_left_y);
-                    let handle_start = (handle_start.x - top_left_x, handle_start.y - top_left_y);
-                    let handle_end = (handle_end.x - top_left_x, handle_end.y - top_left_y);
+                    let start = local(path.start);
+                    let end = local(path.end);
+                    let handle_start = local(handle_start);
+                    let handle_end = local(handle_end);
 
                     imageproc::drawing::draw_cubic_bezier_curve_mut(
                         image,
-                        (start.0 as f32, start.1 as f32),
-                        (end.0 as f32, end.1 as f32),
-                        (handle_start.0 as f32, handle_start.1 as f32),
-                        (handle_end.0 as f32, handle_end.1 as f32),
+                        (start.x as f32, start.y as f32),
+                        (end.x as f32, end.y as f32),
+                        (handle_start.x as f32, handle_start.y as f32),
+                        (handle_end.x as f32, handle_end.y as f32),
                         color,
                     );
                 }
@@ -1076,87 +1818,144 @@ impl JigsawPiece {
         }
     }
 
+    /// Returns `true` if both pieces share an edge, i.e. they are neighbours in the puzzle
+    pub fn beside(&self, other: &JigsawPiece) -> bool {
+        self.index != other.index && self.edges.iter().any(|edge| other.edges.contains(edge))
+    }
+
+    #[deprecated(since = "0.2.0", note = "use `JigsawPiece::edge(Side::Top)`")]
+    pub fn top_edge(&self) -> Option<&Edge> {
+        self.edge(Side::Top)
+    }
+
+    #[deprecated(since = "0.2.0", note = "use `JigsawPiece::edge(Side::Right)`")]
+    pub fn right_edge(&self) -> Option<&Edge> {
+        self.edge(Side::Right)
+    }
+
+    #[deprecated(since = "0.2.0", note = "use `JigsawPiece::edge(Side::Bottom)`")]
+    pub fn bottom_edge(&self) -> Option<&Edge> {
+        self.edge(Side::Bottom)
+    }
+
+    #[deprecated(since = "0.2.0", note = "use `JigsawPiece::edge(Side::Left)`")]
+    pub fn left_edge(&self) -> Option<&Edge> {
+        self.edge(Side::Left)
+    }
+
+    /// Returns `true` if the edge on `side` of this rectangular piece is the edge on
+    /// `other_side` of the other one
+    fn joins_on(&self, side: Side, other: &JigsawPiece, other_side: Side) -> bool {
+        self.edge(side)
+            .is_some_and(|edge| other.edge(other_side) == Some(edge))
+    }
+
+    #[deprecated(since = "0.2.0", note = "use `JigsawTemplate::neighbors`")]
+    pub fn on_the_left_side(&self, other: &JigsawPiece) -> bool {
+        self.joins_on(Side::Right, other, Side::Left)
+    }
+
+    #[deprecated(since = "0.2.0", note = "use `JigsawTemplate::neighbors`")]
+    pub fn on_the_right_side(&self, other: &JigsawPiece) -> bool {
+        self.joins_on(Side::Left, other, Side::Right)
+    }
+
+    #[deprecated(since = "0.2.0", note = "use `JigsawTemplate::neighbors`")]
+    pub fn on_the_top_side(&self, other: &JigsawPiece) -> bool {
+        self.joins_on(Side::Bottom, other, Side::Top)
+    }
+
+    #[deprecated(since = "0.2.0", note = "use `JigsawTemplate::neighbors`")]
+    pub fn on_the_bottom_side(&self, other: &JigsawPiece) -> bool {
+        self.joins_on(Side::Top, other, Side::Bottom)
+    }
+
+    #[deprecated(since = "0.2.0", note = "use `JigsawPiece::is_placed_beside`")]
+    #[allow(deprecated)]
     pub fn is_on_the_left_side(
         &self,
         other: &JigsawPiece,
         self_loc: (f32, f32),
         other_loc: (f32, f32),
     ) -> bool {
-        if (self_loc.0 + self.width - other_loc.0).abs() < COMPARE_THRESHOLD
+        (self_loc.0 + self.width - other_loc.0).abs() < COMPARE_THRESHOLD
             && (self_loc.1 - other_loc.1).abs() < COMPARE_THRESHOLD
-        {
-            self.on_the_left_side(other)
-        } else {
-            false
-        }
-    }
-
-    pub fn on_the_left_side(&self, other: &JigsawPiece) -> bool {
-        self.right_edge == other.left_edge
+            && self.on_the_left_side(other)
     }
 
+    #[deprecated(since = "0.2.0", note = "use `JigsawPiece::is_placed_beside`")]
+    #[allow(deprecated)]
     pub fn is_on_the_right_side(
         &self,
         other: &JigsawPiece,
         self_loc: (f32, f32),
         other_loc: (f32, f32),
     ) -> bool {
-        if (other_loc.0 + other.width - self_loc.0).abs() < COMPARE_THRESHOLD
+        (other_loc.0 + other.width - self_loc.0).abs() < COMPARE_THRESHOLD
             && (self_loc.1 - other_loc.1).abs() < COMPARE_THRESHOLD
-        {
-            self.on_the_right_side(other)
-        } else {
-            false
-        }
-    }
-
-    pub fn on_the_right_side(&self, other: &JigsawPiece) -> bool {
-        self.left_edge == other.right_edge
+            && self.on_the_right_side(other)
     }
 
+    #[deprecated(since = "0.2.0", note = "use `JigsawPiece::is_placed_beside`")]
+    #[allow(deprecated)]
     pub fn is_on_the_top_side(
         &self,
         other: &JigsawPiece,
         self_loc: (f32, f32),
         other_loc: (f32, f32),
     ) -> bool {
-        if (other_loc.1 + other.height - self_loc.1).abs() < COMPARE_THRESHOLD
+        (other_loc.1 + other.height - self_loc.1).abs() < COMPARE_THRESHOLD
             && (self_loc.0 - other_loc.0).abs() < COMPARE_THRESHOLD
-        {
-            self.on_the_top_side(other)
-        } else {
-            false
-        }
-    }
-
-    pub fn on_the_top_side(&self, other: &JigsawPiece) -> bool {
-        self.bottom_edge == other.top_edge
+            && self.on_the_top_side(other)
     }
 
+    #[deprecated(since = "0.2.0", note = "use `JigsawPiece::is_placed_beside`")]
+    #[allow(deprecated)]
     pub fn is_on_the_bottom_side(
         &self,
         other: &JigsawPiece,
         self_loc: (f32, f32),
         other_loc: (f32, f32),
     ) -> bool {
-        if (other_loc.1 - other.height - self_loc.1).abs() < COMPARE_THRESHOLD
+        (other_loc.1 - other.height - self_loc.1).abs() < COMPARE_THRESHOLD
             && (self_loc.0 - other_loc.0).abs() < COMPARE_THRESHOLD
-        {
-            self.on_the_bottom_side(other)
-        } else {
-            false
-        }
+            && self.on_the_bottom_side(other)
     }
 
-    pub fn on_the_bottom_side(&self, other: &JigsawPiece) -> bool {
-        self.top_edge == other.bottom_edge
+    /// Returns the offset of this piece's location from the location of `other` when both are
+    /// joined, in world coordinates with the y-axis pointing up
+    pub fn snap_offset(&self, other: &JigsawPiece) -> (f32, f32) {
+        (
+            self.start_point.0 - other.start_point.0,
+            other.start_point.1 - self.start_point.1,
+        )
     }
 
-    pub fn beside(&self, other: &JigsawPiece) -> bool {
-        self.on_the_top_side(other)
-            || self.on_the_bottom_side(other)
-            || self.on_the_left_side(other)
-            || self.on_the_right_side(other)
+    /// Returns `true` if `other` is a neighbour of this piece and the given locations of both
+    /// pieces (in world coordinates) are close enough to their joined position to snap them
+    /// together
+    pub fn is_placed_beside(
+        &self,
+        other: &JigsawPiece,
+        self_loc: (f32, f32),
+        other_loc: (f32, f32),
+    ) -> bool {
+        self.is_near_joined_position(other, self_loc, other_loc) && self.beside(other)
+    }
+
+    /// Returns `true` if the given locations of both pieces (in world coordinates) are close
+    /// enough to their joined position to snap them together. Unlike
+    /// [`JigsawPiece::is_placed_beside`] it doesn't check whether they are neighbours, look them
+    /// up with [`JigsawTemplate::adjacent`] instead.
+    pub fn is_near_joined_position(
+        &self,
+        other: &JigsawPiece,
+        self_loc: (f32, f32),
+        other_loc: (f32, f32),
+    ) -> bool {
+        let (offset_x, offset_y) = self.snap_offset(other);
+        (self_loc.0 - other_loc.0 - offset_x).abs() < COMPARE_THRESHOLD
+            && (self_loc.1 - other_loc.1 - offset_y).abs() < COMPARE_THRESHOLD
     }
 
     pub fn is_boarder(&self) -> bool {
@@ -1165,6 +1964,7 @@ impl JigsawPiece {
 
     /// Checks if a given point is inside the puzzle piece
     /// Trick: Check if the point is inside the rotated subpath. If not, check if it is inside the original subpath
+    #[cfg(test)]
     fn contains(&self, point: DVec2) -> bool {
         self.subpath.point_inside(
             point,
@@ -1206,6 +2006,79 @@ impl JigsawPiece {
     }
 }
 
+/// Builds the closed contour of a piece from its edges in clockwise order. Each edge is reversed
+/// if necessary to continue where the previous one ends.
+fn contour(edges: &[Edge]) -> Subpath<PuzzleId> {
+    let touches =
+        |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).abs() < 0.01 && (a.1 - b.1).abs() < 0.01;
+    let mut beziers = vec![];
+    let mut position = None;
+    for (i, edge) in edges.iter().enumerate() {
+        let reverse = match position {
+            Some(position) => !touches(edge.starting_point(), position),
+            // the first edge runs towards the second one
+            None => edges.get(i + 1).is_some_and(|next| {
+                touches(edge.starting_point(), next.starting_point())
+                    || touches(edge.starting_point(), next.end_point())
+            }),
+        };
+        position = Some(if reverse {
+            edge.starting_point()
+        } else {
+            edge.end_point()
+        });
+        beziers.extend(edge.to_beziers(reverse));
+    }
+    Subpath::from_beziers(&beziers, true)
+}
+
+/// The serialised form of a [`JigsawPiece`], i.e. everything except the subpath
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct JigsawPieceData {
+    index: usize,
+    start_point: (f32, f32),
+    width: f32,
+    height: f32,
+    top_left_x: u32,
+    top_left_y: u32,
+    crop_width: u32,
+    crop_height: u32,
+    edges: Vec<Edge>,
+    is_boarder: bool,
+}
+
+/// Rejects indented edges without segments, which have no starting and end point
+#[cfg(feature = "serde")]
+fn non_empty_segments<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<IndentationSegment>, D::Error> {
+    let segments = Vec::<IndentationSegment>::deserialize(deserializer)?;
+    if segments.is_empty() {
+        return Err(serde::de::Error::invalid_length(0, &"at least one segment"));
+    }
+    Ok(segments)
+}
+
+#[cfg(feature = "serde")]
+impl From<JigsawPieceData> for JigsawPiece {
+    fn from(data: JigsawPieceData) -> Self {
+        JigsawPiece {
+            index: data.index,
+            start_point: data.start_point,
+            subpath: contour(&data.edges),
+            width: data.width,
+            height: data.height,
+            top_left_x: data.top_left_x,
+            top_left_y: data.top_left_y,
+            crop_width: data.crop_width,
+            crop_height: data.crop_height,
+            edges: data.edges,
+            is_boarder: data.is_boarder,
+        }
+    }
+}
+
 const COMPARE_THRESHOLD: f32 = 10.0;
 
 #[derive(Clone, PartialEq, Hash, Eq, Debug)]
@@ -1217,7 +2090,9 @@ impl Identifier for PuzzleId {
     }
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+/// The sides of a rectangular piece, in the order of [`JigsawPiece::edges`]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Side {
     Top,
     Right,
@@ -1287,4 +2162,969 @@ mod tests {
             Some((6, 4))
         );
     }
+
+    #[test]
+    fn test_target_pieces() {
+        let generator =
+            JigsawGenerator::with_target_pieces(DynamicImage::new_rgba8(1920, 1080), 300).unwrap();
+        assert_eq!(
+            (generator.pieces_in_column(), generator.pieces_in_row()),
+            (25, 12)
+        );
+        let generator =
+            JigsawGenerator::with_target_pieces(DynamicImage::new_rgba8(1080, 1920), 300).unwrap();
+        assert_eq!(
+            (generator.pieces_in_column(), generator.pieces_in_row()),
+            (12, 25)
+        );
+        assert!(matches!(
+            JigsawGenerator::with_target_pieces(DynamicImage::new_rgba8(1920, 1080), 0),
+            Err(JigsawError::ZeroPieces { .. })
+        ));
+    }
+
+    #[test]
+    fn test_svg_export() {
+        let image = DynamicImage::new_rgba8(400, 300);
+        let template = JigsawGenerator::new(image, 4, 3)
+            .seed(1)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        // 4 * 3 pieces share 4 * 4 horizontal and 5 * 3 vertical edges
+        assert_eq!(template.unique_edges().len(), 31);
+
+        let svg = template
+            .to_svg(&SvgOptions::default().stroke_width(2.5).embed_image(true))
+            .unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r#"viewBox="0 0 400 300""#));
+        assert!(svg.contains(r#"stroke-width="2.5""#));
+        assert!(svg.contains("data:image/png;base64,"));
+        assert_eq!(svg.matches("<path ").count(), 31);
+        let svg = template
+            .to_svg(&SvgOptions::default().stroke_color(r#"red" onload="alert(1)"#))
+            .unwrap();
+        assert!(svg.contains(r#"stroke="red&quot; onload=&quot;alert(1)""#));
+
+        let path = template.pieces[0].to_svg_path();
+        assert!(path.starts_with("M0 0 L100 0"));
+        assert!(path.ends_with('Z'));
+    }
+
+    #[test]
+    fn test_laser_export() {
+        let image = DynamicImage::new_rgba8(400, 300);
+        let template = JigsawGenerator::new(image, 4, 3)
+            .seed(1)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        // a pixel is half a millimetre
+        let options = LaserOptions::default().dpi(50.8).kerf(2.0);
+
+        // the 14 edges on the image border form one loop, the other 17 edges are cut once
+        let svg = template.to_laser_svg(&options).unwrap();
+        assert!(svg.contains(r#"width="202.2mm" height="152.2mm""#));
+        let (cuts, border) = svg.split_once(r#"<g id="border""#).unwrap();
+        assert_eq!(cuts.matches("<path ").count(), 17);
+        assert_eq!(border.matches("<path ").count(), 1);
+
+        let border_vertices = |dxf: &str| {
+            let lines: Vec<&str> = dxf.lines().collect();
+            let mut vertices = vec![];
+            for (i, pair) in lines.chunks_exact(2).enumerate() {
+                if pair == ["0", "VERTEX"] && lines[2 * i + 3] == "BORDER" {
+                    let x: f64 = lines[2 * i + 5].parse().unwrap();
+                    let y: f64 = lines[2 * i + 7].parse().unwrap();
+                    vertices.push(DVec2::new(x, y));
+                }
+            }
+            vertices
+        };
+        let dxf = template.to_dxf(&options).unwrap();
+        assert!(dxf.ends_with("0\nEOF\n"));
+        assert_eq!(dxf.matches("POLYLINE\n8\nCUT\n").count(), 17);
+        assert_eq!(dxf.matches("POLYLINE\n8\nBORDER\n").count(), 1);
+        let grown = border_vertices(&dxf);
+        assert!(grown.iter().all(|v| (v.x + 1.0).abs() < 1e-3
+            || (v.x - 201.0).abs() < 1e-3
+            || (v.y + 1.0).abs() < 1e-3
+            || (v.y - 151.0).abs() < 1e-3));
+        for corner in [(-1.0, -1.0), (201.0, -1.0), (201.0, 151.0), (-1.0, 151.0)] {
+            assert!(grown.iter().any(|v| v.distance(corner.into()) < 1e-3));
+        }
+
+        let dxf = template.to_dxf(&options.clone().kerf(-2.0)).unwrap();
+        // R12 has no $INSUNITS
+        assert!(dxf.contains("AC1009") && !dxf.contains("$INSUNITS"));
+        let shrunk = border_vertices(&dxf);
+        assert!(shrunk
+            .iter()
+            .all(|v| v.x > 0.99 && v.x < 199.01 && v.y > 0.99 && v.y < 149.01));
+
+        assert!(matches!(
+            template.to_dxf(&options.dpi(0.0)),
+            Err(JigsawError::InvalidDpi(_))
+        ));
+    }
+
+    /// Compares the edges of a template against a golden file in `tests/golden`. Run the tests
+    /// with `UPDATE_GOLDEN=1` to rewrite the golden files after an intended change.
+    fn assert_golden_edges(name: &str, template: &JigsawTemplate) {
+        let actual: String = template
+            .unique_edges()
+            .iter()
+            .map(|edge| edge.to_svg_path() + "\n")
+            .collect();
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(name);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, &actual).unwrap();
+        }
+        let expected = std::fs::read_to_string(&path).unwrap();
+        assert!(actual == expected, "edges differ from {}", path.display());
+    }
+
+    #[test]
+    fn test_golden_edges() {
+        let template = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3)
+            .seed(42)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        assert_golden_edges("classic_400x300_4x3_seed42.golden", &template);
+
+        let template = JigsawGenerator::new(DynamicImage::new_rgba8(1000, 700), 5, 7)
+            .seed(3_000_000_000)
+            .tab_size(25.0)
+            .jitter(13.0)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        assert_golden_edges("classic_1000x700_5x7_large_seed.golden", &template);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let generate = |game_mode| {
+            JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3)
+                .seed(5)
+                .generate(game_mode, false)
+                .unwrap()
+        };
+        let template = generate(GameMode::Classic);
+        assert_eq!(
+            template.neighbors(5),
+            [
+                Some((Side::Top, 1)),
+                Some((Side::Right, 6)),
+                Some((Side::Bottom, 9)),
+                Some((Side::Left, 4))
+            ]
+        );
+        assert_eq!(
+            template.neighbors(0),
+            [None, Some((Side::Right, 1)), Some((Side::Bottom, 4)), None]
+        );
+        // the deprecated side checks agree with the neighbours
+        #[allow(deprecated)]
+        {
+            let pieces = &template.pieces;
+            assert!(pieces[4].on_the_left_side(&pieces[5]));
+            assert!(pieces[6].on_the_right_side(&pieces[5]));
+            assert!(pieces[1].on_the_top_side(&pieces[5]));
+            assert!(pieces[9].on_the_bottom_side(&pieces[5]));
+            assert!(!pieces[5].on_the_left_side(&pieces[4]));
+            assert_eq!(pieces[5].top_edge(), pieces[1].bottom_edge());
+        }
+
+        for game_mode in [GameMode::Classic, GameMode::Hexagon, GameMode::Irregular] {
+            let template = generate(game_mode);
+            for piece in template.pieces.iter() {
+                for other in template.pieces.iter() {
+                    assert_eq!(
+                        template.adjacent(piece.index).any(|i| i == other.index),
+                        piece.beside(other)
+                    );
+                }
+                if piece.edges.len() != 4 {
+                    assert_eq!(template.neighbors(piece.index), [None; 4]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_label_map() {
+        for game_mode in [
+            GameMode::Classic,
+            GameMode::Square,
+            GameMode::Hexagon,
+            GameMode::Irregular,
+        ] {
+            let template = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3)
+                .seed(5)
+                .generate(game_mode, false)
+                .unwrap();
+            let labels = template.label_map();
+            assert_eq!(labels.dimensions(), (400, 300));
+            if matches!(game_mode, GameMode::Classic | GameMode::Square) {
+                assert_eq!(labels.get_pixel(10, 10).0[0], 0);
+                assert_eq!(labels.get_pixel(390, 290).0[0], 11);
+            }
+            assert!(labels
+                .pixels()
+                .all(|label| label.0[0] != NO_PIECE && label.0[0] != OVERLAPPING_PIECES));
+
+            // every piece owns about as many pixels as its area
+            for piece in template.pieces.iter() {
+                let polygon = raster::flatten(&piece.subpath);
+                let area = polygon
+                    .iter()
+                    .zip(polygon.iter().cycle().skip(1))
+                    .map(|(a, b)| a.perp_dot(*b))
+                    .sum::<f64>()
+                    .abs()
+                    / 2.0;
+                let owned = labels
+                    .pixels()
+                    .filter(|label| label.0[0] == piece.index as u32)
+                    .count() as f64;
+                assert!((owned - area).abs() < area * 0.02, "{owned} {area}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        for game_mode in [
+            GameMode::Classic,
+            GameMode::Square,
+            GameMode::Hexagon,
+            GameMode::Irregular,
+        ] {
+            let template = JigsawGenerator::new(DynamicImage::new_rgba8(613, 419), 7, 5)
+                .seed(3)
+                .generate(game_mode, false)
+                .unwrap();
+            template.verify().unwrap();
+        }
+
+        let template = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3)
+            .seed(5)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        let mut clipped = template.clone();
+        clipped.pieces[5].crop_width -= 10;
+        assert!(matches!(
+            clipped.verify(),
+            Err(JigsawError::ClippedPiece { index: 5, .. })
+        ));
+        let mut missing = template.clone();
+        missing.pieces.truncate(11);
+        assert!(matches!(
+            missing.verify(),
+            Err(JigsawError::UnsharedEdge { owners: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_colliding_tabs() {
+        // the tabs of the long edges of flat pieces reach across the pieces
+        for (width, height) in [(600, 100), (100, 600)] {
+            for game_mode in [GameMode::Classic, GameMode::Hexagon, GameMode::Irregular] {
+                for seed in 0..3 {
+                    let template =
+                        JigsawGenerator::new(DynamicImage::new_rgba8(width, height), 3, 4)
+                            .seed(seed)
+                            .tab_size(30.0)
+                            .jitter(13.0)
+                            .generate(game_mode, false)
+                            .unwrap();
+                    template.verify().unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_atlas() {
+        let mut image = RgbaImage::new(400, 300);
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            *pixel = Rgba([x as u8, y as u8, 100, 255]);
+        }
+        let template = JigsawGenerator::new(DynamicImage::ImageRgba8(image), 4, 3)
+            .seed(5)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+
+        let options = AtlasOptions::default();
+        let (layout, pages) = template.pack_atlas(&options).unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(layout.pieces_on_page(0).count(), template.pieces.len());
+        let piece = &template.pieces[5];
+        let rect = layout.rects[5];
+        assert_eq!(
+            (rect.width, rect.height),
+            (piece.crop_width, piece.crop_height)
+        );
+        let cropped = piece
+            .crop_with_options(&template.origin_image, &options.crop)
+            .unwrap()
+            .to_rgba8();
+        for (x, y) in [(0, 0), (rect.width / 2, rect.height / 2)] {
+            assert_eq!(
+                pages[0].color.get_pixel(rect.x + x, rect.y + y),
+                cropped.get_pixel(x, y)
+            );
+        }
+        let [min_u, min_v, max_u, max_v] = layout.uv_rect(5);
+        assert!(0.0 < min_u && min_u < max_u && max_u <= 1.0);
+        assert!(0.0 < min_v && min_v < max_v && max_v <= 1.0);
+
+        // small pages hold only a few pieces, no rectangles overlap or leave their page
+        let options = AtlasOptions::default().max_size(300).padding(3);
+        let layout = template.atlas_layout(&options).unwrap();
+        assert!(layout.page_sizes.len() > 1);
+        for (i, a) in layout.rects.iter().enumerate() {
+            let (width, height) = layout.page_sizes[a.page];
+            assert!(a.x >= 3 && a.x + a.width + 3 <= width);
+            assert!(a.y >= 3 && a.y + a.height + 3 <= height);
+            for b in layout.rects[i + 1..].iter().filter(|b| b.page == a.page) {
+                assert!(
+                    a.x + a.width + 3 <= b.x
+                        || b.x + b.width + 3 <= a.x
+                        || a.y + a.height + 3 <= b.y
+                        || b.y + b.height + 3 <= a.y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_scaled_template() {
+        for game_mode in [GameMode::Classic, GameMode::Hexagon, GameMode::Irregular] {
+            let template = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 5, 4)
+                .seed(7)
+                .generate(game_mode, false)
+                .unwrap();
+            let scaled = template.scaled(2.5).unwrap();
+            assert_eq!(scaled.image_dimensions(), (1000, 750));
+            assert_eq!(scaled.origin_image.dimensions(), (1000, 750));
+            assert_eq!(scaled.piece_dimensions.0, template.piece_dimensions.0 * 2.5);
+            assert_eq!(scaled.adjacency, template.adjacency);
+            scaled.verify().unwrap();
+            for (piece, scaled_piece) in template.pieces.iter().zip(scaled.pieces.iter()) {
+                // both crop rectangles are rounded to whole pixels
+                assert_eq!(scaled_piece.index, piece.index);
+                assert!(
+                    (scaled_piece.top_left_x as f32 - piece.top_left_x as f32 * 2.5).abs() <= 2.5
+                );
+                assert!(
+                    (scaled_piece.crop_width as f32 - piece.crop_width as f32 * 2.5).abs() <= 6.0
+                );
+            }
+        }
+
+        // the size of a proxy image is rounded, the template still covers the original exactly
+        let proxy = JigsawGenerator::new(DynamicImage::new_rgba8(333, 233), 5, 4)
+            .seed(7)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        let full = proxy
+            .with_origin_image(DynamicImage::new_rgba8(1001, 701))
+            .unwrap();
+        assert_eq!(full.image_dimensions(), (1001, 701));
+        full.verify().unwrap();
+
+        assert!(matches!(
+            proxy.with_origin_image(DynamicImage::new_rgba8(1000, 1000)),
+            Err(JigsawError::MismatchedImage { .. })
+        ));
+        assert!(matches!(
+            proxy.scaled(0.0),
+            Err(JigsawError::InvalidScale(_))
+        ));
+    }
+
+    #[test]
+    fn test_alpha_silhouette() {
+        // an opaque disc on a transparent background
+        let mut image = RgbaImage::new(400, 300);
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let (dx, dy) = (x as f32 - 200.0, y as f32 - 150.0);
+            if dx * dx + dy * dy < 120.0 * 120.0 {
+                *pixel = Rgba([200, 100, 50, 255]);
+            }
+        }
+        for game_mode in [GameMode::Classic, GameMode::Hexagon, GameMode::Irregular] {
+            let template = JigsawGenerator::new(DynamicImage::ImageRgba8(image.clone()), 8, 6)
+                .seed(4)
+                .alpha_threshold(0)
+                .generate(game_mode, false)
+                .unwrap();
+            assert!(template.pieces.len() < 48);
+            assert_eq!(template.alpha_threshold, Some(0));
+            assert_eq!(template.island_count(), 1);
+            for (i, piece) in template.pieces.iter().enumerate() {
+                assert_eq!(piece.index, i);
+                for neighbor in template.adjacent(i) {
+                    assert!(template.adjacent(neighbor).any(|other| other == i));
+                }
+            }
+            // the pieces in the middle of the disc are no border pieces
+            let labels = template.label_map();
+            let center = labels.get_pixel(200, 150).0[0] as usize;
+            assert!(!template.pieces[center].is_boarder());
+            assert!(template.pieces.iter().any(|piece| piece.is_boarder()));
+            template.verify().unwrap();
+
+            // even border pieces may only lack the neighbours which were dropped
+            let mut pieces = template.pieces.clone();
+            pieces.remove(center);
+            for (i, piece) in pieces.iter_mut().enumerate() {
+                piece.index = i;
+                piece.is_boarder = true;
+            }
+            let mut gap = JigsawTemplate::new(
+                pieces,
+                template.origin_image.clone(),
+                template.piece_dimensions,
+                template.number_of_pieces,
+                None,
+            );
+            gap.alpha_threshold = template.alpha_threshold;
+            assert!(matches!(
+                gap.verify(),
+                Err(JigsawError::UnsharedEdge { owners: 1, .. })
+            ));
+        }
+
+        // opaque images keep all pieces
+        let template = JigsawGenerator::new(DynamicImage::new_rgb8(400, 300), 8, 6)
+            .seed(4)
+            .alpha_threshold(0)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        assert_eq!(template.pieces.len(), 48);
+        assert_eq!(
+            template.pieces.iter().filter(|p| p.is_boarder()).count(),
+            24
+        );
+    }
+
+    #[test]
+    fn test_wrap_around() {
+        for game_mode in [GameMode::Classic, GameMode::Square] {
+            let template = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3)
+                .seed(6)
+                .warp(20.0)
+                .wrap_around(true)
+                .generate(game_mode, false)
+                .unwrap();
+            let wrap_around = template.wrap_around.unwrap();
+            assert_eq!((wrap_around.width, wrap_around.height), (400, 300));
+            assert_eq!(
+                template.origin_image.dimensions(),
+                (400 + 2 * wrap_around.margin, 300 + 2 * wrap_around.margin)
+            );
+            template.verify().unwrap();
+            for (i, piece) in template.pieces.iter().enumerate() {
+                assert!(!piece.is_boarder());
+                assert_eq!(template.adjacent(i).count(), 4);
+                for neighbor in template.adjacent(i) {
+                    assert!(template.adjacent(neighbor).any(|other| other == i));
+                }
+            }
+            // the last piece of the first row joins the first one on its right side
+            let [_, right, _, left] = template.neighbors(3);
+            assert_eq!(right, Some((Side::Right, 0)));
+            assert_eq!(left, Some((Side::Left, 2)));
+            let (start, first_start) = (
+                template.pieces[3].start_point,
+                template.pieces[0].start_point,
+            );
+            let offset = (start.0 - 400.0 - first_start.0, first_start.1 - start.1);
+            assert_eq!(template.join_offsets(3, 0), vec![offset]);
+            let near = (offset.0 + 3.0, offset.1 - 2.0);
+            assert_eq!(template.snap_offset(3, 0, near, (0.0, 0.0)), Some(offset));
+            assert_eq!(template.snap_offset(3, 0, (300.0, 0.0), (0.0, 0.0)), None);
+
+            let scaled = template.scaled(0.5).unwrap();
+            assert_eq!(scaled.wrap_around.unwrap().width, 200);
+            assert_eq!(scaled.adjacency, template.adjacency);
+            scaled.verify().unwrap();
+        }
+
+        assert!(matches!(
+            JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3)
+                .wrap_around(true)
+                .generate(GameMode::Hexagon, false),
+            Err(JigsawError::UnsupportedWrapAround(GameMode::Hexagon))
+        ));
+    }
+
+    #[test]
+    fn test_hexagon_layout() {
+        let template = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3)
+            .seed(5)
+            .generate(GameMode::Hexagon, false)
+            .unwrap();
+        // two full rows of 4 pieces and one shifted row of 3 full and 2 half pieces
+        assert_eq!(template.pieces.len(), 13);
+        assert!(template.pieces[4].is_boarder());
+        assert!(!template.pieces[6].is_boarder());
+
+        for piece in template.pieces.iter() {
+            // the contour is closed and every edge starts where the previous one ends
+            let first = piece.subpath.iter().next().unwrap().start;
+            let last = piece.subpath.iter().last().unwrap().end;
+            assert!(first.distance(last) < 0.01);
+
+            for edge in piece.edges.iter() {
+                let owners = template
+                    .pieces
+                    .iter()
+                    .filter(|other| other.edges.contains(edge))
+                    .count();
+                match edge {
+                    Edge::IndentedEdge(_) => assert_eq!(owners, 2),
+                    Edge::StraightEdge(_) => assert!(piece.is_boarder()),
+                }
+            }
+        }
+
+        // an inner piece has six neighbours
+        let inner = &template.pieces[6];
+        let neighbours = template.pieces.iter().filter(|p| inner.beside(p)).count();
+        assert_eq!(neighbours, 6);
+    }
+
+    #[test]
+    fn test_warped_grid() {
+        let image = DynamicImage::new_rgba8(400, 300);
+        let template = JigsawGenerator::new(image, 4, 3)
+            .seed(3)
+            .warp(20.0)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        assert_eq!(template.unique_edges().len(), 31);
+
+        // the corners between the pieces are shifted, the corners of the image are not
+        let corners: Vec<(f32, f32)> = template.pieces.iter().map(|p| p.start_point).collect();
+        assert_eq!(corners[0], (0.0, 0.0));
+        assert_eq!(corners[1].1, 0.0);
+        assert_eq!(corners[4].0, 0.0);
+        assert_ne!(corners[5], (100.0, 100.0));
+        assert!((corners[5].0 - 100.0).abs() <= 20.0 && (corners[5].1 - 100.0).abs() <= 20.0);
+
+        let mut area = 0.0;
+        for piece in template.pieces.iter() {
+            area += piece
+                .subpath
+                .iter()
+                .map(|bezier| bezier.start.perp_dot(bezier.end))
+                .sum::<f64>()
+                / 2.0;
+            for side in [Side::Top, Side::Right, Side::Bottom, Side::Left] {
+                let edge = piece.edge(side).unwrap();
+                let owners = template
+                    .pieces
+                    .iter()
+                    .filter(|other| other.edges.contains(edge))
+                    .count();
+                assert!(owners == 2 || piece.is_boarder());
+            }
+        }
+        assert!((area - 400.0 * 300.0).abs() < 1.0);
+        assert!(template.pieces[5].beside(&template.pieces[6]));
+        assert!(template.pieces[5].beside(&template.pieces[9]));
+    }
+
+    #[test]
+    fn test_edge_profiles() {
+        /// A straight line, which is the simplest possible profile
+        #[derive(Debug)]
+        struct LineProfile;
+
+        impl EdgeProfile for LineProfile {
+            fn segments(&self, _shape: &TabShape) -> Vec<ProfileSegment> {
+                vec![[(0.25, 0.0), (0.75, 0.0), (1.0, 0.0)]]
+            }
+        }
+
+        let generator = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3).seed(9);
+        let classic = generator.generate(GameMode::Classic, false).unwrap();
+        let default = generator
+            .clone()
+            .edge_profile(ClassicProfile)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        assert_eq!(classic.unique_edges(), default.unique_edges());
+
+        let line = generator
+            .clone()
+            .edge_profile(LineProfile)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        let Edge::IndentedEdge(edge) = line.pieces[5].edge(Side::Top).unwrap() else {
+            panic!("inner edges are indented");
+        };
+        assert_eq!(edge.segments.len(), 1);
+        assert_eq!(edge.segments[0].control_point_1, (125.0, 100.0));
+
+        /// A broken profile without any segments
+        #[derive(Debug)]
+        struct EmptyProfile;
+
+        impl EdgeProfile for EmptyProfile {
+            fn segments(&self, _shape: &TabShape) -> Vec<ProfileSegment> {
+                vec![]
+            }
+        }
+
+        for mode in [GameMode::Classic, GameMode::Hexagon, GameMode::Irregular] {
+            let result = generator
+                .clone()
+                .edge_profile(EmptyProfile)
+                .generate(mode, false);
+            assert!(matches!(result, Err(JigsawError::EmptyProfile)));
+        }
+
+        for (profile, segments) in [
+            (Arc::new(DoubleTabProfile) as Arc<dyn EdgeProfile>, 5),
+            (Arc::new(NubProfile), 5),
+            (Arc::new(ArrowProfile), 8),
+            (Arc::new(WavyProfile), 2),
+        ] {
+            let mut generator = generator.clone();
+            generator.edge_profile = profile;
+            let template = generator.generate(GameMode::Hexagon, false).unwrap();
+            for piece in template.pieces.iter() {
+                let first = piece.subpath.iter().next().unwrap().start;
+                let last = piece.subpath.iter().last().unwrap().end;
+                assert!(first.distance(last) < 0.01);
+                for edge in piece.edges.iter() {
+                    if let Edge::IndentedEdge(edge) = edge {
+                        assert_eq!(edge.segments.len(), segments);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_antialiased_crop() {
+        let image =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(400, 300, Rgba([10, 20, 30, 255])));
+        let template = JigsawGenerator::new(image, 4, 3)
+            .seed(2)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        let piece = &template.pieces[5];
+        let jagged = piece.crop(&template.origin_image).to_rgba8();
+        let smooth = piece
+            .crop_with_options(&template.origin_image, &CropOptions::default())
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(jagged.dimensions(), smooth.dimensions());
+
+        let alpha_sum = |image: &RgbaImage| image.pixels().map(|p| p.0[3] as f64).sum::<f64>();
+        assert!(jagged.pixels().all(|p| p.0[3] == 0 || p.0[3] == 255));
+        assert!(smooth.pixels().any(|p| p.0[3] > 0 && p.0[3] < 255));
+        assert!((alpha_sum(&jagged) / alpha_sum(&smooth) - 1.0).abs() < 0.02);
+
+        let white = piece.fill_white(&smooth.clone().into()).to_rgba8();
+        for (w, s) in white.pixels().zip(smooth.pixels()) {
+            assert_eq!(w.0[3], s.0[3]);
+        }
+    }
+
+    #[test]
+    fn test_scaled_crop() {
+        let image =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(400, 300, Rgba([10, 20, 30, 255])));
+        let template = JigsawGenerator::new(image, 4, 3)
+            .seed(2)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        let piece = &template.pieces[5];
+        let alpha_sum = |image: &RgbaImage| image.pixels().map(|p| p.0[3] as f64).sum::<f64>();
+        let full = alpha_sum(
+            &piece
+                .crop_scaled(&template.origin_image, 1.0)
+                .unwrap()
+                .to_rgba8(),
+        );
+        for factor in [0.5, 2.0] {
+            let scaled = piece
+                .crop_scaled(&template.origin_image, factor)
+                .unwrap()
+                .to_rgba8();
+            assert_eq!(scaled.dimensions(), piece.crop_size(factor));
+            let expected = full * (factor * factor) as f64;
+            assert!((alpha_sum(&scaled) / expected - 1.0).abs() < 0.05);
+        }
+
+        let options = AtlasOptions::default().bevel(Some(BevelOptions::default()));
+        let lods = template
+            .pack_atlas_lods(&options, &[0.5, 1.0, 2.0])
+            .unwrap();
+        assert_eq!(lods.len(), 3);
+        assert_eq!(lods[1].0, template.atlas_layout(&options).unwrap());
+        for ((layout, pages), factor) in lods.iter().zip([0.5, 1.0, 2.0]) {
+            let rect = layout.rects[5];
+            assert_eq!((rect.width, rect.height), piece.crop_size(factor));
+            assert_eq!(pages.len(), layout.page_sizes.len());
+        }
+
+        for factor in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(
+                piece.crop_scaled(&template.origin_image, factor),
+                Err(JigsawError::InvalidScale(_))
+            ));
+            let options = options.clone().crop(CropOptions::default().scale(factor));
+            assert!(matches!(
+                template.atlas_layout(&options),
+                Err(JigsawError::InvalidScale(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_bevel() {
+        let mut image = RgbaImage::from_pixel(40, 40, Rgba([100, 100, 100, 255]));
+        image.put_pixel(20, 0, Rgba([100, 100, 100, 0]));
+        image.put_pixel(20, 39, Rgba([100, 100, 100, 128]));
+        let shaded = BevelOptions::default()
+            .apply(&image.clone().into())
+            .to_rgba8();
+
+        for (s, i) in shaded.pixels().zip(image.pixels()) {
+            assert_eq!(s.0[3], i.0[3]);
+        }
+        // the light comes from the top left corner
+        assert!(shaded.get_pixel(1, 20).0[0] > 100);
+        assert!(shaded.get_pixel(20, 1).0[0] > 100);
+        assert!(shaded.get_pixel(38, 20).0[0] < 100);
+        assert!(shaded.get_pixel(20, 38).0[0] < 100);
+        assert_eq!(shaded.get_pixel(20, 20).0, [100, 100, 100, 255]);
+
+        let from_right = BevelOptions::default()
+            .light_angle(0.0)
+            .apply(&image.into())
+            .to_rgba8();
+        assert!(from_right.get_pixel(1, 20).0[0] < 100);
+        assert!(from_right.get_pixel(38, 20).0[0] > 100);
+    }
+
+    #[test]
+    fn test_scanline_coverage() {
+        for game_mode in [
+            GameMode::Square,
+            GameMode::Classic,
+            GameMode::Hexagon,
+            GameMode::Irregular,
+        ] {
+            let template = JigsawGenerator::new(DynamicImage::new_rgba8(300, 200), 3, 2)
+                .seed(4)
+                .generate(game_mode, false)
+                .unwrap();
+            let mut samples = 0;
+            let mut ambiguous = 0;
+            for piece in template.pieces.iter() {
+                let polygon = raster::flatten(&piece.subpath);
+                let top_left = DVec2::new(piece.top_left_x as f64, piece.top_left_y as f64);
+                let coverage =
+                    raster::coverage(&polygon, top_left, piece.crop_width, piece.crop_height, 1);
+                let distance_to_contour = |point: DVec2| {
+                    polygon
+                        .iter()
+                        .zip(polygon.iter().cycle().skip(1))
+                        .map(|(a, b)| {
+                            let t = ((point - *a).dot(*b - *a) / a.distance_squared(*b))
+                                .clamp(0.0, 1.0);
+                            point.distance(*a + (*b - *a) * t)
+                        })
+                        .fold(f64::MAX, f64::min)
+                };
+                for y in 0..piece.crop_height {
+                    for x in 0..piece.crop_width {
+                        let point = top_left + DVec2::new(x as f64, y as f64);
+                        let inside = coverage[(y * piece.crop_width + x) as usize] == 1.0;
+                        samples += 1;
+                        if inside == piece.contains(point) {
+                            continue;
+                        }
+                        // the per-pixel test is unreliable for points on the contour and for
+                        // points on the same row as an anchor, where its two ray tests disagree
+                        assert!(
+                            distance_to_contour(point) < 0.5
+                                || piece.subpath.point_inside(point)
+                                    != piece.subpath.contains_point(point),
+                            "{game_mode:?} piece {} differs at {point}",
+                            piece.index
+                        );
+                        ambiguous += 1;
+                    }
+                }
+            }
+            assert!(ambiguous * 1000 < samples);
+        }
+    }
+
+    #[test]
+    fn test_irregular_layout() {
+        let generator = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3).seed(11);
+        let template = generator.generate(GameMode::Irregular, false).unwrap();
+        assert_eq!(template.pieces.len(), 12);
+
+        let mut area = 0.0;
+        for piece in template.pieces.iter() {
+            let first = piece.subpath.iter().next().unwrap().start;
+            let last = piece.subpath.iter().last().unwrap().end;
+            assert!(first.distance(last) < 0.01);
+
+            // tabs and blanks cancel each other out, so the contours add up to the image area
+            area += piece
+                .subpath
+                .iter()
+                .map(|bezier| bezier.start.perp_dot(bezier.end))
+                .sum::<f64>()
+                / 2.0;
+
+            let neighbours = template.pieces.iter().filter(|p| piece.beside(p)).count();
+            assert!(neighbours >= 2);
+            for edge in piece.edges.iter() {
+                let owners = template
+                    .pieces
+                    .iter()
+                    .filter(|other| other.edges.contains(edge))
+                    .count();
+                assert!(owners == 2 || piece.is_boarder());
+            }
+        }
+        assert!((area - 400.0 * 300.0).abs() < 1.0);
+
+        let again = generator.generate(GameMode::Irregular, false).unwrap();
+        assert_eq!(template.unique_edges(), again.unique_edges());
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let generator = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3);
+        let generate = |generator: JigsawGenerator| generator.generate(GameMode::Square, false);
+        assert!(matches!(
+            generate(generator.clone().tab_size(40.0)),
+            Err(JigsawError::InvalidTabSize(40.0))
+        ));
+        assert!(matches!(
+            generate(generator.clone().jitter(-1.0)),
+            Err(JigsawError::InvalidJitter(-1.0))
+        ));
+        assert!(matches!(
+            generate(generator.clone().warp(30.0)),
+            Err(JigsawError::InvalidWarp(30.0))
+        ));
+        assert!(matches!(
+            generate(JigsawGenerator::new(
+                DynamicImage::new_rgba8(400, 300),
+                0,
+                3
+            )),
+            Err(JigsawError::ZeroPieces { .. })
+        ));
+        assert!(matches!(
+            generate(JigsawGenerator::new(
+                DynamicImage::new_rgba8(400, 300),
+                100,
+                3
+            )),
+            Err(JigsawError::ImageTooSmall { .. })
+        ));
+        assert!(matches!(
+            JigsawGenerator::from_rgba8(2, 2, &[0; 15], 1, 1),
+            Err(JigsawError::InvalidRawBuffer { len: 15, .. })
+        ));
+        assert!(matches!(
+            EdgeContourGenerator::new(10.0, 10.0, Some(5.0), None, None),
+            Err(JigsawError::InvalidTabSize(5.0))
+        ));
+        assert!(generate(generator).is_ok());
+    }
+
+    #[test]
+    fn test_output_resolution() {
+        let generator = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3);
+        let dimensions = |generator: JigsawGenerator, resize: bool| {
+            let template = generator.generate(GameMode::Square, resize).unwrap();
+            assert_eq!(
+                template.image_dimensions(),
+                template.origin_image.dimensions()
+            );
+            template.origin_image.dimensions()
+        };
+        assert_eq!(dimensions(generator.clone(), true), (400, 300));
+        assert_eq!(dimensions(generator.clone(), false), (400, 300));
+        let small = generator.clone().max_dimensions(200, 200);
+        assert_eq!(dimensions(small.clone(), true), (200, 150));
+        assert_eq!(dimensions(small, false), (400, 300));
+        let exact = generator
+            .clone()
+            .target_size(800, 500)
+            .resampling(FilterType::Nearest);
+        assert_eq!(dimensions(exact.clone(), false), (800, 500));
+        assert_eq!(
+            dimensions(exact.crop_to_aspect_ratio(1.0), true),
+            (800, 500)
+        );
+        assert_eq!(
+            dimensions(generator.clone().crop_to_aspect_ratio(1.0), false),
+            (300, 300)
+        );
+        assert_eq!(
+            dimensions(generator.clone().crop_to_aspect_ratio(2.0), false),
+            (400, 200)
+        );
+        assert!(matches!(
+            generator
+                .crop_to_aspect_ratio(0.0)
+                .generate(GameMode::Square, false),
+            Err(JigsawError::InvalidAspectRatio(_))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let image = DynamicImage::new_rgba8(400, 300);
+        let template = JigsawGenerator::new(image, 4, 3)
+            .seed(7)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        let json = serde_json::to_string(&template).unwrap();
+        let loaded: JigsawTemplate = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.adjacency, template.adjacency);
+
+        // templates without the adjacency get it rebuilt
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value.as_object_mut().unwrap().remove("adjacency");
+        let rebuilt: JigsawTemplate = serde_json::from_value(value).unwrap();
+        assert_eq!(rebuilt.adjacency, template.adjacency);
+
+        assert_eq!(loaded.origin_image.dimensions(), (0, 0));
+        assert_eq!(loaded.image_dimensions(), (400, 300));
+        assert_eq!(loaded.number_of_pieces, template.number_of_pieces);
+        for (a, b) in template.pieces.iter().zip(loaded.pieces.iter()) {
+            assert_eq!(a.edges, b.edges);
+            assert_eq!(a.subpath, b.subpath);
+            assert_eq!(a.crop_width, b.crop_width);
+        }
+
+        // an indented edge without segments has no starting and end point
+        let Edge::IndentedEdge(edge) = template.pieces[5].edge(Side::Top).unwrap() else {
+            panic!("inner edges are indented");
+        };
+        let mut value = serde_json::to_value(edge).unwrap();
+        value["segments"] = serde_json::json!([]);
+        assert!(serde_json::from_value::<IndentedEdge>(value).is_err());
+    }
 }
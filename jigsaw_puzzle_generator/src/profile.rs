@@ -0,0 +1,187 @@
+//! Shapes of the tabs on [`IndentedEdge`](crate::IndentedEdge)s
+//!
+//! An [`EdgeProfile`] describes an edge in a normalised frame. The first coordinate of a point
+//! runs along the edge from `0.0` at its starting point to `1.0` at its end point, the second one
+//! runs across the edge towards the tab. Both are relative to the size of the edge, and the
+//! [`EdgeContourGenerator`](crate::EdgeContourGenerator) maps them onto the image and mirrors
+//! them for the edges with a blank on the other side.
+
+use std::fmt::Debug;
+
+/// The random parameters of a single edge, drawn by the
+/// [`EdgeContourGenerator`](crate::EdgeContourGenerator) for every new edge
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TabShape {
+    /// The tab size factor between `0.05` and `0.15`
+    pub tab_size: f32,
+    /// The mirrored `e` value of the previous edge, so consecutive edges may join smoothly
+    pub a: f32,
+    /// Random value between the negative and the positive jitter factor
+    pub b: f32,
+    /// Random value between the negative and the positive jitter factor
+    pub c: f32,
+    /// Random value between the negative and the positive jitter factor
+    pub d: f32,
+    /// Random value between the negative and the positive jitter factor
+    pub e: f32,
+}
+
+/// A cubic Bézier segment of a profile given by its first control point, its second control
+/// point and its end point. It starts at the end point of the previous segment, the first segment
+/// starts at `(0.0, 0.0)`.
+pub type ProfileSegment = [(f32, f32); 3];
+
+/// Describes the geometry of the tabs on [`IndentedEdge`](crate::IndentedEdge)s. Select a
+/// profile with [`JigsawGenerator::edge_profile`](crate::JigsawGenerator::edge_profile).
+pub trait EdgeProfile: Debug + Send + Sync {
+    /// Returns the segments of an edge with the given random parameters. There has to be at least
+    /// one segment and the last one has to end at `(1.0, 0.0)`.
+    fn segments(&self, shape: &TabShape) -> Vec<ProfileSegment>;
+}
+
+/// The classic rounded tab, which is the default profile
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClassicProfile;
+
+impl EdgeProfile for ClassicProfile {
+    fn segments(&self, shape: &TabShape) -> Vec<ProfileSegment> {
+        let TabShape {
+            tab_size: t,
+            a,
+            b,
+            c,
+            d,
+            e,
+        } = *shape;
+        vec![
+            [(0.2, a), (0.5 + b + d, -t + c), (0.5 - t + b, t + c)],
+            [
+                (0.5 - 2.0 * t + b - d, 3.0 * t + c),
+                (0.5 + 2.0 * t + b - d, 3.0 * t + c),
+                (0.5 + t + b, t + c),
+            ],
+            [(0.5 + b + d, -t + b + d), (0.8, e), (1.0, 0.0)],
+        ]
+    }
+}
+
+/// Two smaller rounded tabs at a third and at two thirds of the edge
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DoubleTabProfile;
+
+impl EdgeProfile for DoubleTabProfile {
+    fn segments(&self, shape: &TabShape) -> Vec<ProfileSegment> {
+        let TabShape {
+            tab_size,
+            a,
+            b,
+            c,
+            d,
+            e,
+        } = *shape;
+        let t = tab_size * 0.6;
+        let first = 1.0 / 3.0 + b / 2.0;
+        let second = 2.0 / 3.0 + b / 2.0;
+        let tab = |m: f32| {
+            [
+                (m - 2.0 * t - d, 3.0 * t + c),
+                (m + 2.0 * t - d, 3.0 * t + c),
+                (m + t, t + c),
+            ]
+        };
+        vec![
+            [(0.1, a), (first + d, -t + c), (first - t, t + c)],
+            tab(first),
+            [
+                (first + d, -t + c),
+                (second + d, -t + c),
+                (second - t, t + c),
+            ],
+            tab(second),
+            [(second + d, -t + c), (0.9, e), (1.0, 0.0)],
+        ]
+    }
+}
+
+/// A square nub with straight sides
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NubProfile;
+
+impl EdgeProfile for NubProfile {
+    fn segments(&self, shape: &TabShape) -> Vec<ProfileSegment> {
+        let TabShape {
+            tab_size: t, b, c, ..
+        } = *shape;
+        let (left, right) = (0.5 - t + b, 0.5 + t + b);
+        let height = 2.0 * t + c;
+        polyline(&[
+            (left, 0.0),
+            (left, height),
+            (right, height),
+            (right, 0.0),
+            (1.0, 0.0),
+        ])
+    }
+}
+
+/// An arrow head on a narrow neck
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArrowProfile;
+
+impl EdgeProfile for ArrowProfile {
+    fn segments(&self, shape: &TabShape) -> Vec<ProfileSegment> {
+        let TabShape {
+            tab_size: t, b, c, ..
+        } = *shape;
+        let middle = 0.5 + b;
+        let neck = 0.6 * t;
+        let (base, tip) = (1.2 * t + c, 3.0 * t + c);
+        polyline(&[
+            (middle - neck, 0.0),
+            (middle - neck, base),
+            (middle - 2.0 * t, base),
+            (middle, tip),
+            (middle + 2.0 * t, base),
+            (middle + neck, base),
+            (middle + neck, 0.0),
+            (1.0, 0.0),
+        ])
+    }
+}
+
+/// A smooth wave without a tab. The pieces don't interlock, but the edges still only fit to
+/// each other.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WavyProfile;
+
+impl EdgeProfile for WavyProfile {
+    fn segments(&self, shape: &TabShape) -> Vec<ProfileSegment> {
+        let TabShape {
+            tab_size: t,
+            a,
+            b,
+            c,
+            d,
+            e,
+        } = *shape;
+        let amplitude = 1.5 * t + c;
+        vec![
+            [(0.2, a), (0.3 + b, amplitude), (0.5 + b, d)],
+            // mirrors the previous control point at the turning point for a smooth wave
+            [(0.7 + b, 2.0 * d - amplitude), (0.8, e), (1.0, 0.0)],
+        ]
+    }
+}
+
+/// Connects the given points with straight segments, starting at `(0.0, 0.0)`
+fn polyline(points: &[(f32, f32)]) -> Vec<ProfileSegment> {
+    let mut start = (0.0, 0.0);
+    points
+        .iter()
+        .map(|&end| {
+            let segment = [start, end, end];
+            start = end;
+            segment
+        })
+        .collect()
+}
@@ -0,0 +1,147 @@
+//! Scanline rasterisation of piece contours
+//!
+//! A contour is flattened into a polygon once, and every row of samples is then filled between
+//! the sorted crossings of the row with the polygon, following the even-odd rule. This replaces
+//! an inside test against the Bézier curves for every single pixel.
+
+use crate::PuzzleId;
+use bezier_rs::{Bezier, BezierHandles, Subpath};
+use glam::DVec2;
+#[cfg(feature = "parallel")]
+use rayon::{iter::IndexedParallelIterator, iter::ParallelIterator, slice::ParallelSliceMut};
+
+/// Maximum distance in pixels between a flattened contour and its Bézier curves
+const FLATTEN_TOLERANCE: f64 = 0.01;
+
+/// Returns the corners of a polygon which approximates the closed contour
+pub(crate) fn flatten(subpath: &Subpath<PuzzleId>) -> Vec<DVec2> {
+    flatten_beziers(subpath.iter())
+}
+
+/// Returns the points of a polyline which approximates the connected curves, including the end
+/// point of the last one
+pub(crate) fn flatten_open(beziers: &[Bezier]) -> Vec<DVec2> {
+    let mut polyline = flatten_beziers(beziers.iter().copied());
+    polyline.extend(beziers.last().map(|bezier| bezier.end));
+    polyline
+}
+
+/// Returns the points of the curves without their end points
+fn flatten_beziers(beziers: impl Iterator<Item = Bezier>) -> Vec<DVec2> {
+    let mut points = vec![];
+    for bezier in beziers {
+        let segments = segment_count(&bezier);
+        points.extend((0..segments).map(|i| evaluate(&bezier, i as f64 / segments as f64)));
+    }
+    points
+}
+
+/// Returns the number of straight segments which approximate the curve within the
+/// [`FLATTEN_TOLERANCE`], following Wang's formula
+fn segment_count(bezier: &Bezier) -> usize {
+    let (degree_factor, second_difference) = match bezier.handles {
+        BezierHandles::Linear => return 1,
+        BezierHandles::Quadratic { handle } => {
+            (0.25, (bezier.start - 2.0 * handle + bezier.end).length())
+        }
+        BezierHandles::Cubic {
+            handle_start,
+            handle_end,
+        } => (
+            0.75,
+            (bezier.start - 2.0 * handle_start + handle_end)
+                .length()
+                .max((handle_start - 2.0 * handle_end + bezier.end).length()),
+        ),
+    };
+    ((degree_factor * second_difference / FLATTEN_TOLERANCE)
+        .sqrt()
+        .ceil() as usize)
+        .max(1)
+}
+
+/// Returns the point of the curve at the parameter `t`
+fn evaluate(bezier: &Bezier, t: f64) -> DVec2 {
+    let s = 1.0 - t;
+    match bezier.handles {
+        BezierHandles::Linear => bezier.start * s + bezier.end * t,
+        BezierHandles::Quadratic { handle } => {
+            bezier.start * s * s + handle * 2.0 * s * t + bezier.end * t * t
+        }
+        BezierHandles::Cubic {
+            handle_start,
+            handle_end,
+        } => {
+            bezier.start * s * s * s
+                + handle_start * 3.0 * s * s * t
+                + handle_end * 3.0 * s * t * t
+                + bezier.end * t * t * t
+        }
+    }
+}
+
+/// Returns the share of every pixel covered by the polygon, for an area of `width` x `height`
+/// pixels whose top left corner is at `origin`, row by row.
+///
+/// With one sample per pixel the pixel is tested at its top left corner, otherwise it is
+/// divided into `samples` x `samples` cells which are tested at their centres.
+pub(crate) fn coverage(
+    polygon: &[DVec2],
+    origin: DVec2,
+    width: u32,
+    height: u32,
+    samples: u32,
+) -> Vec<f32> {
+    let samples = samples.max(1);
+    let step = 1.0 / samples as f64;
+    let offset = if samples == 1 { 0.0 } else { step / 2.0 };
+    let edges: Vec<(DVec2, DVec2)> = polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .filter(|(a, b)| a.y != b.y)
+        .map(|(a, b)| (*a - origin, *b - origin))
+        .collect();
+
+    let mut coverage = vec![0.0; (width * height) as usize];
+    if width == 0 {
+        return coverage;
+    }
+    #[cfg(feature = "parallel")]
+    let rows = coverage.par_chunks_mut(width as usize);
+    #[cfg(not(feature = "parallel"))]
+    let rows = coverage.chunks_mut(width as usize);
+    rows.enumerate().for_each(|(row, pixels)| {
+        let mut counts = vec![0u32; width as usize];
+        let mut crossings = vec![];
+        for j in 0..samples {
+            let y = row as f64 + j as f64 * step + offset;
+            find_crossings(&edges, y, &mut crossings);
+            for span in crossings.chunks_exact(2) {
+                // the samples of the row are at `i * step + offset` for i in first..last
+                let first = ((span[0] - offset) / step).ceil().max(0.0) as u64;
+                let last = ((span[1] - offset) / step).ceil().max(0.0) as u64;
+                let last = last.min(width as u64 * samples as u64);
+                for i in first..last {
+                    counts[(i / samples as u64) as usize] += 1;
+                }
+            }
+        }
+        for (pixel, count) in pixels.iter_mut().zip(counts) {
+            *pixel = count as f32 / (samples * samples) as f32;
+        }
+    });
+    coverage
+}
+
+/// Replaces `crossings` with the sorted x coordinates where the horizontal line at `y` crosses
+/// the given segments. A segment includes its upper but not its lower end, so a line through a
+/// corner of a polygon crosses it twice or not at all.
+pub(crate) fn find_crossings(segments: &[(DVec2, DVec2)], y: f64, crossings: &mut Vec<f64>) {
+    crossings.clear();
+    crossings.extend(segments.iter().filter_map(|(a, b)| {
+        let (top, bottom) = if a.y < b.y { (a, b) } else { (b, a) };
+        (y >= top.y && y < bottom.y)
+            .then(|| top.x + (y - top.y) * (bottom.x - top.x) / (bottom.y - top.y))
+    }));
+    crossings.sort_by(f64::total_cmp);
+}
@@ -0,0 +1,170 @@
+//! Scaling of templates to another resolution
+//!
+//! The edges are the only geometry of a template, everything else is derived from them. So the
+//! points of the edges are scaled and the pieces are built again for the scaled image, which
+//! yields the same template as if it was generated at that resolution. A template which wraps
+//! around is scaled without its margin, which is padded again for the new resolution.
+
+use crate::{
+    Edge, IndentationSegment, IndentedEdge, JigsawError, JigsawPiece, JigsawResult, JigsawTemplate,
+    StraightEdge, WrapAround,
+};
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
+use std::sync::Arc;
+
+impl Edge {
+    /// Returns the edge with all points scaled by `factor_x` along the x-axis and by `factor_y`
+    /// along the y-axis
+    pub fn scaled(&self, factor_x: f32, factor_y: f32) -> Edge {
+        self.map_points(|(x, y)| (x * factor_x, y * factor_y))
+    }
+
+    /// Returns the edge with the given function applied to all of its points
+    pub(crate) fn map_points(&self, map: impl Fn((f32, f32)) -> (f32, f32)) -> Edge {
+        match self {
+            Edge::IndentedEdge(edge) => Edge::IndentedEdge(IndentedEdge {
+                segments: edge
+                    .segments
+                    .iter()
+                    .map(|segment| IndentationSegment {
+                        starting_point: map(segment.starting_point),
+                        end_point: map(segment.end_point),
+                        control_point_1: map(segment.control_point_1),
+                        control_point_2: map(segment.control_point_2),
+                    })
+                    .collect(),
+            }),
+            Edge::StraightEdge(edge) => Edge::StraightEdge(StraightEdge {
+                starting_point: map(edge.starting_point),
+                end_point: map(edge.end_point),
+            }),
+        }
+    }
+}
+
+impl JigsawTemplate {
+    /// Returns the template scaled by `factor`, with the image resized alike. The pieces keep
+    /// their indices, shapes and neighbours, only their edges, contours, crop rectangles and the
+    /// piece dimensions change.
+    ///
+    /// This allows to generate a template on a small proxy of an image, which is fast, and use
+    /// it for the full image later, see [`JigsawTemplate::with_origin_image`].
+    pub fn scaled(&self, factor: f32) -> JigsawResult<JigsawTemplate> {
+        if !(factor.is_finite() && factor > 0.0) {
+            return Err(JigsawError::InvalidScale(factor));
+        }
+        let (width, height) = self.puzzle_dimensions();
+        let size = (
+            ((width as f32 * factor).round() as u32).max(1),
+            ((height as f32 * factor).round() as u32).max(1),
+        );
+        let image = match self.wrap_around {
+            _ if self.origin_image.width() == 0 || self.origin_image.height() == 0 => {
+                DynamicImage::new_rgba8(0, 0)
+            }
+            Some(wrap_around) => self
+                .origin_image
+                .crop_imm(
+                    wrap_around.margin,
+                    wrap_around.margin,
+                    wrap_around.width,
+                    wrap_around.height,
+                )
+                .resize_exact(size.0, size.1, FilterType::Lanczos3),
+            None => self
+                .origin_image
+                .resize_exact(size.0, size.1, FilterType::Lanczos3),
+        };
+        self.scaled_to(size, image)
+    }
+
+    /// Returns the template scaled to the given image, which replaces the image of the template.
+    /// The image has to have the aspect ratio of the template, up to the rounding of the size
+    /// of a proxy image the template was generated on. The template is scaled along both axes
+    /// separately, so its pieces cover the image exactly. If the template wraps around, the image
+    /// is the one without the margin, see [`JigsawTemplate::wrap_around`].
+    pub fn with_origin_image(&self, image: DynamicImage) -> JigsawResult<JigsawTemplate> {
+        let (width, height) = self.puzzle_dimensions();
+        let (new_width, new_height) = image.dimensions();
+        // a proxy image is at most one of its pixels smaller than the image scaled down
+        let factor = new_width.max(new_height) as f32 / width.max(height).max(1) as f32;
+        let expected = (width as f32 * factor, height as f32 * factor);
+        if (expected.0 - new_width as f32).abs() > factor.max(1.0)
+            || (expected.1 - new_height as f32).abs() > factor.max(1.0)
+        {
+            return Err(JigsawError::MismatchedImage {
+                width: new_width,
+                height: new_height,
+                template_width: width,
+                template_height: height,
+            });
+        }
+        self.scaled_to((new_width, new_height), image)
+    }
+
+    /// Returns the size of the puzzle, which is the size of the image without the margin of a
+    /// template which wraps around
+    fn puzzle_dimensions(&self) -> (u32, u32) {
+        match self.wrap_around {
+            Some(wrap_around) => (wrap_around.width, wrap_around.height),
+            None => self.image_dimensions(),
+        }
+    }
+
+    /// Scales the template to a puzzle of the given size, showing the given image
+    fn scaled_to(
+        &self,
+        (new_width, new_height): (u32, u32),
+        image: DynamicImage,
+    ) -> JigsawResult<JigsawTemplate> {
+        let (width, height) = self.puzzle_dimensions();
+        let factor_x = new_width as f32 / width as f32;
+        let factor_y = new_height as f32 / height as f32;
+        let wrap_around = self.wrap_around.map(|wrap_around| WrapAround {
+            margin: (wrap_around.margin as f32 * factor_x.max(factor_y)).ceil() as u32,
+            width: new_width,
+            height: new_height,
+        });
+        let scale_point = |point: (f32, f32)| match (&self.wrap_around, &wrap_around) {
+            (Some(wrap_around), Some(scaled)) => wrap_around.scale_point(scaled, point),
+            _ => (point.0 * factor_x, point.1 * factor_y),
+        };
+        let (origin_image, image_size) = match &wrap_around {
+            Some(wrap_around) if image.width() > 0 && image.height() > 0 => {
+                (wrap_around.pad_image(&image), wrap_around.image_size())
+            }
+            Some(wrap_around) => (image, wrap_around.image_size()),
+            None => (image, (new_width, new_height)),
+        };
+        let pieces = self
+            .pieces
+            .iter()
+            .map(|piece| {
+                JigsawPiece::new(
+                    piece.index,
+                    scale_point(piece.start_point),
+                    image_size,
+                    (piece.width * factor_x, piece.height * factor_y),
+                    piece
+                        .edges
+                        .iter()
+                        .map(|edge| edge.map_points(scale_point))
+                        .collect(),
+                    piece.is_boarder,
+                )
+            })
+            .collect::<JigsawResult<Vec<_>>>()?;
+        Ok(JigsawTemplate {
+            pieces,
+            origin_image: Arc::new(origin_image),
+            piece_dimensions: (
+                self.piece_dimensions.0 * factor_x,
+                self.piece_dimensions.1 * factor_y,
+            ),
+            number_of_pieces: self.number_of_pieces,
+            adjacency: self.adjacency.clone(),
+            alpha_threshold: self.alpha_threshold,
+            wrap_around,
+        })
+    }
+}
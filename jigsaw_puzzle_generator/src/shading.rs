@@ -0,0 +1,174 @@
+//! Bevel and emboss shading of cropped piece images
+//!
+//! The distance of every pixel to the transparent surroundings of the piece is taken as a height
+//! map, which rises along the contour and is flat in the middle of the piece. The slopes are lit
+//! from the configured direction, an inner shadow darkens the piece along the contour facing away
+//! from the light and the rim on the lit side gets a highlight.
+
+use image::{DynamicImage, GrayImage, Luma};
+use imageproc::distance_transform::euclidean_squared_distance_transform;
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelIterator;
+
+/// Options for [`BevelOptions::apply`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BevelOptions {
+    /// Direction the light comes from in degrees, counterclockwise from the right side of the
+    /// screen. Defaults to `135.0`, i.e. the top left corner.
+    pub light_angle: f32,
+    /// Width of the bevel along the contour in pixel. Defaults to `4.0`
+    pub bevel_width: f32,
+    /// How much the bevel is lightened on the lit and darkened on the opposite side, between
+    /// `0.0` and `1.0`. Defaults to `0.35`
+    pub bevel_strength: f32,
+    /// How far the inner shadow reaches from the contour facing away from the light into the
+    /// piece in pixel. Defaults to `3.0`
+    pub shadow_offset: f32,
+    /// How much the inner shadow darkens the piece, between `0.0` and `1.0`. Defaults to `0.3`
+    pub shadow_strength: f32,
+    /// Width of the highlight on the lit rim of the piece in pixel. Defaults to `1.5`
+    pub highlight_width: f32,
+    /// How much the rim is lightened, between `0.0` and `1.0`. Defaults to `0.5`
+    pub highlight_strength: f32,
+}
+
+impl Default for BevelOptions {
+    fn default() -> Self {
+        BevelOptions {
+            light_angle: 135.0,
+            bevel_width: 4.0,
+            bevel_strength: 0.35,
+            shadow_offset: 3.0,
+            shadow_strength: 0.3,
+            highlight_width: 1.5,
+            highlight_strength: 0.5,
+        }
+    }
+}
+
+impl BevelOptions {
+    pub fn light_angle(mut self, light_angle: f32) -> Self {
+        self.light_angle = light_angle;
+        self
+    }
+
+    pub fn bevel_width(mut self, bevel_width: f32) -> Self {
+        self.bevel_width = bevel_width;
+        self
+    }
+
+    pub fn bevel_strength(mut self, bevel_strength: f32) -> Self {
+        self.bevel_strength = bevel_strength;
+        self
+    }
+
+    pub fn shadow_offset(mut self, shadow_offset: f32) -> Self {
+        self.shadow_offset = shadow_offset;
+        self
+    }
+
+    pub fn shadow_strength(mut self, shadow_strength: f32) -> Self {
+        self.shadow_strength = shadow_strength;
+        self
+    }
+
+    pub fn highlight_width(mut self, highlight_width: f32) -> Self {
+        self.highlight_width = highlight_width;
+        self
+    }
+
+    pub fn highlight_strength(mut self, highlight_strength: f32) -> Self {
+        self.highlight_strength = highlight_strength;
+        self
+    }
+
+    /// Returns the options for an image of the piece scaled by `factor`, with all widths and
+    /// offsets scaled alike
+    pub fn scaled(&self, factor: f32) -> Self {
+        BevelOptions {
+            bevel_width: self.bevel_width * factor,
+            shadow_offset: self.shadow_offset * factor,
+            highlight_width: self.highlight_width * factor,
+            ..self.clone()
+        }
+    }
+
+    /// Shades a cropped piece image with a bevel, an inner shadow and a highlight along the
+    /// contour given by its alpha channel. The alpha values are kept.
+    pub fn apply(&self, image: &DynamicImage) -> DynamicImage {
+        let mut shaded = image.to_rgba8();
+        let (width, height) = shaded.dimensions();
+
+        // the transparent pixels and a frame around the image are the surroundings of the piece
+        let mut outside = GrayImage::new(width + 2, height + 2);
+        for (x, y, pixel) in outside.enumerate_pixels_mut() {
+            let is_frame = x == 0 || y == 0 || x == width + 1 || y == height + 1;
+            if is_frame || shaded.get_pixel(x - 1, y - 1).0[3] < 128 {
+                *pixel = Luma([255]);
+            }
+        }
+        let squared_distances = euclidean_squared_distance_transform(&outside);
+        let distance = |x: f32, y: f32| -> f32 {
+            let (x, y) = (x.round() + 1.0, y.round() + 1.0);
+            if x < 0.0 || y < 0.0 || x >= (width + 2) as f32 || y >= (height + 2) as f32 {
+                0.0
+            } else {
+                squared_distances.get_pixel(x as u32, y as u32).0[0].sqrt() as f32
+            }
+        };
+
+        let angle = self.light_angle.to_radians();
+        // towards the light in image coordinates, where the y axis points down
+        let light = (angle.cos(), -angle.sin());
+        let bevel_width = self.bevel_width.max(f32::EPSILON);
+        let ramp = |x: f32, y: f32| distance(x, y).min(bevel_width);
+        #[cfg(feature = "parallel")]
+        let pixels = shaded.par_enumerate_pixels_mut();
+        #[cfg(not(feature = "parallel"))]
+        let pixels = shaded.enumerate_pixels_mut();
+        pixels.for_each(|(x, y, pixel)| {
+            if pixel.0[3] == 0 {
+                return;
+            }
+            let (x, y) = (x as f32, y as f32);
+            let d = distance(x, y);
+
+            // the slope rises towards the inside of the piece, the side facing the light is lit
+            let slope = (
+                (ramp(x + 1.0, y) - ramp(x - 1.0, y)) / 2.0,
+                (ramp(x, y + 1.0) - ramp(x, y - 1.0)) / 2.0,
+            );
+            let facing = -(slope.0 * light.0 + slope.1 * light.1);
+            let mut shade = facing * self.bevel_strength;
+
+            if d <= self.highlight_width {
+                let length = (slope.0 * slope.0 + slope.1 * slope.1).sqrt();
+                if length > 0.0 {
+                    shade += (facing / length).max(0.0) * self.highlight_strength;
+                }
+            }
+
+            if self.shadow_offset > 0.0 {
+                let away_from_light = distance(
+                    x - light.0 * self.shadow_offset,
+                    y - light.1 * self.shadow_offset,
+                );
+                let shadow = 1.0 - (away_from_light / self.shadow_offset).min(1.0);
+                shade -= shadow * self.shadow_strength;
+            }
+
+            let shade = shade.clamp(-1.0, 1.0);
+            for channel in pixel.0.iter_mut().take(3) {
+                let value = *channel as f32;
+                *channel = if shade > 0.0 {
+                    value + (255.0 - value) * shade
+                } else {
+                    value * (1.0 + shade)
+                }
+                .round() as u8;
+            }
+        });
+
+        shaded.into()
+    }
+}
@@ -0,0 +1,82 @@
+//! Pieces for images with a transparent background
+//!
+//! The pieces are laid out over the whole image as usual. Afterwards every pixel is assigned to
+//! its piece with the label map, and the pieces without any opaque pixel are dropped. The crops
+//! keep the alpha values of the image, so the remaining pieces along the silhouette follow it.
+
+use crate::label::{NO_PIECE, OVERLAPPING_PIECES};
+use crate::JigsawTemplate;
+use image::GenericImageView;
+use log::debug;
+
+impl JigsawTemplate {
+    /// Drops the pieces which only cover pixels with an alpha value up to the threshold, marks
+    /// the pieces along the silhouette as border pieces and numbers the remaining pieces again
+    pub(crate) fn follow_silhouette(self, threshold: u8) -> JigsawTemplate {
+        let labels = self.label_map();
+        let mut opaque = vec![0usize; self.pieces.len()];
+        let mut transparent = vec![0usize; self.pieces.len()];
+        for ((_, _, pixel), label) in self.origin_image.pixels().zip(labels.pixels()) {
+            match label.0[0] {
+                NO_PIECE | OVERLAPPING_PIECES => {}
+                index if pixel.0[3] > threshold => opaque[index as usize] += 1,
+                index => transparent[index as usize] += 1,
+            }
+        }
+
+        let dropped: Vec<bool> = opaque.iter().map(|&count| count == 0).collect();
+        debug!(
+            "dropping {} transparent pieces",
+            dropped.iter().filter(|&&dropped| dropped).count()
+        );
+        let mut pieces = vec![];
+        for (i, mut piece) in self.pieces.into_iter().enumerate() {
+            if dropped[i] {
+                continue;
+            }
+            piece.is_boarder = piece.is_boarder
+                || transparent[i] > 0
+                || self.adjacency[i]
+                    .iter()
+                    .flatten()
+                    .any(|&neighbor| dropped[neighbor]);
+            piece.index = pieces.len();
+            pieces.push(piece);
+        }
+
+        let mut template = JigsawTemplate::new(
+            pieces,
+            self.origin_image,
+            self.piece_dimensions,
+            self.number_of_pieces,
+            self.wrap_around,
+        );
+        template.alpha_threshold = Some(threshold);
+        template
+    }
+
+    /// Returns the number of groups of pieces which are connected by shared edges. This is `1`
+    /// unless pieces of a transparent background split the puzzle, see
+    /// [`crate::JigsawGenerator::alpha_threshold`].
+    pub fn island_count(&self) -> usize {
+        let mut visited = vec![false; self.pieces.len()];
+        let mut islands = 0;
+        for start in 0..self.pieces.len() {
+            if visited[start] {
+                continue;
+            }
+            islands += 1;
+            visited[start] = true;
+            let mut stack = vec![start];
+            while let Some(index) = stack.pop() {
+                for neighbor in self.adjacent(index) {
+                    if !visited[neighbor] {
+                        visited[neighbor] = true;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+        islands
+    }
+}
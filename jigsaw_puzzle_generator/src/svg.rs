@@ -0,0 +1,219 @@
+//! SVG serialisation of jigsaw templates, pieces and edges
+
+use crate::{round, Edge, JigsawPiece, JigsawResult, JigsawTemplate};
+use bezier_rs::{Bezier, BezierHandles};
+use glam::DVec2;
+use image::ImageFormat;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::io::Cursor;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Options for [`JigsawTemplate::to_svg`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    /// Width of the cut lines in pixel. Defaults to `1.0`
+    pub stroke_width: f32,
+    /// Color of the cut lines. Any SVG color value is accepted, it is escaped for the attribute.
+    /// Defaults to `black`
+    pub stroke_color: String,
+    /// Whether the template image is embedded as a PNG data URI below the cut lines. Defaults to
+    /// `false`
+    pub embed_image: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            stroke_width: 1.0,
+            stroke_color: "black".to_string(),
+            embed_image: false,
+        }
+    }
+}
+
+impl SvgOptions {
+    pub fn stroke_width(mut self, stroke_width: f32) -> Self {
+        self.stroke_width = stroke_width;
+        self
+    }
+
+    pub fn stroke_color(mut self, stroke_color: impl Into<String>) -> Self {
+        self.stroke_color = stroke_color.into();
+        self
+    }
+
+    pub fn embed_image(mut self, embed_image: bool) -> Self {
+        self.embed_image = embed_image;
+        self
+    }
+}
+
+impl Edge {
+    /// Returns the SVG path data (the `d` attribute) of the edge, starting with a move-to command
+    pub fn to_svg_path(&self) -> String {
+        let beziers = self.to_beziers(false);
+        let mut path = String::new();
+        if let Some(first) = beziers.first() {
+            write_move_to(&mut path, first.start);
+        }
+        for bezier in beziers.iter() {
+            write_bezier(&mut path, bezier);
+        }
+        path
+    }
+}
+
+impl JigsawPiece {
+    /// Returns the SVG path data (the `d` attribute) of the closed piece contour
+    pub fn to_svg_path(&self) -> String {
+        let mut path = String::new();
+        for (i, bezier) in self.subpath.iter().enumerate() {
+            if i == 0 {
+                write_move_to(&mut path, bezier.start);
+            }
+            write_bezier(&mut path, &bezier);
+        }
+        path.push('Z');
+        path
+    }
+}
+
+impl JigsawTemplate {
+    /// Returns the edges of all pieces, each shared edge only once, in piece order
+    pub fn unique_edges(&self) -> Vec<&Edge> {
+        let mut seen = HashSet::new();
+        let mut edges = vec![];
+        for piece in self.pieces.iter() {
+            for edge in piece.edges.iter() {
+                if seen.insert(edge_key(edge)) {
+                    edges.push(edge);
+                }
+            }
+        }
+        edges
+    }
+
+    /// Serialises the cut lines of the template into a standalone SVG document. The viewBox is
+    /// sized to the template image and every edge is emitted exactly once as its own `<path>`.
+    pub fn to_svg(&self, options: &SvgOptions) -> JigsawResult<String> {
+        let (width, height) = self.image_dimensions();
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        )?;
+        if options.embed_image && self.origin_image.width() > 0 {
+            let mut png = vec![];
+            self.origin_image
+                .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+            writeln!(
+                svg,
+                r#"  <image x="0" y="0" width="{width}" height="{height}" href="data:image/png;base64,{}"/>"#,
+                base64_encode(&png)
+            )?;
+        }
+        writeln!(
+            svg,
+            r#"  <g fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round">"#,
+            escape_attribute(&options.stroke_color),
+            options.stroke_width
+        )?;
+        for edge in self.unique_edges() {
+            writeln!(svg, r#"    <path d="{}"/>"#, edge.to_svg_path())?;
+        }
+        writeln!(svg, "  </g>")?;
+        writeln!(svg, "</svg>")?;
+        Ok(svg)
+    }
+}
+
+/// The end points of an edge in hundredths of a pixel, see [`edge_key`]
+pub(crate) type EdgeKey = ((i64, i64), (i64, i64));
+
+/// Identifies an edge by its end points regardless of its direction. The coordinates are already
+/// rounded to two decimal places, so they are compared in hundredths of a pixel.
+pub(crate) fn edge_key(edge: &Edge) -> EdgeKey {
+    let to_key = |(x, y): (f32, f32)| ((x * 100.0).round() as i64, (y * 100.0).round() as i64);
+    let start = to_key(edge.starting_point());
+    let end = to_key(edge.end_point());
+    if start <= end {
+        (start, end)
+    } else {
+        (end, start)
+    }
+}
+
+pub(crate) fn write_move_to(path: &mut String, point: DVec2) {
+    let _ = write!(path, "M{} {}", coord(point.x), coord(point.y));
+}
+
+pub(crate) fn write_bezier(path: &mut String, bezier: &Bezier) {
+    let _ = match bezier.handles {
+        BezierHandles::Linear => write!(path, " L{} {}", coord(bezier.end.x), coord(bezier.end.y)),
+        BezierHandles::Quadratic { handle } => write!(
+            path,
+            " Q{} {} {} {}",
+            coord(handle.x),
+            coord(handle.y),
+            coord(bezier.end.x),
+            coord(bezier.end.y)
+        ),
+        BezierHandles::Cubic {
+            handle_start,
+            handle_end,
+        } => write!(
+            path,
+            " C{} {} {} {} {} {}",
+            coord(handle_start.x),
+            coord(handle_start.y),
+            coord(handle_end.x),
+            coord(handle_end.y),
+            coord(bezier.end.x),
+            coord(bezier.end.y)
+        ),
+    };
+}
+
+/// Escapes the characters which would end an XML attribute value or start markup
+fn escape_attribute(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Formats a coordinate with at most two decimal places
+fn coord(value: f64) -> f32 {
+    round(value as f32)
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
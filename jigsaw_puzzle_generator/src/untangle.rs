@@ -0,0 +1,310 @@
+//! Removal of colliding tabs
+//!
+//! With a high jitter or narrow pieces, the tab of an edge may cross another edge of the same
+//! piece, or even itself, so the contour of the piece is no simple closed curve anymore. Every
+//! such edge is created again with new random values, from a sequence which is derived from the
+//! seed but independent of the one of the generator. If that doesn't help, its tab is shrunk
+//! step by step, and as a last resort the edge becomes straight. Edges are checked in the order
+//! of the pieces, so the result only depends on the seed. The copies of an edge on opposite
+//! borders of a puzzle which wraps around are replaced together.
+
+use crate::svg::{edge_key, EdgeKey};
+use crate::wrap::shared_edge_key;
+use crate::{
+    lattice_edge, raster, Edge, EdgeContourGenerator, GameMode, IndentedEdge, JigsawGenerator,
+    JigsawPiece, JigsawResult, StraightEdge, WrapAround,
+};
+use glam::DVec2;
+use log::debug;
+use std::collections::HashMap;
+
+/// How many times a colliding edge is created again with new random values
+const MAX_REROLLS: usize = 4;
+/// How many times the tab of a colliding edge is shrunk before the edge becomes straight
+const MAX_SHRINKS: usize = 6;
+/// Factor the tab of a colliding edge is shrunk by in every step
+const SHRINK_FACTOR: f32 = 0.75;
+/// How many times all edges are checked at most
+const MAX_PASSES: usize = 4;
+/// Maximum distance in pixels from a shared corner of two edges at which they may touch
+const CORNER_TOLERANCE: f64 = 0.001;
+
+impl JigsawGenerator {
+    /// Replaces the indented edges which intersect themselves or another edge of a piece they
+    /// belong to, and rebuilds the pieces with replaced edges
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn untangle(
+        &self,
+        mut pieces: Vec<JigsawPiece>,
+        game_mode: GameMode,
+        image_size: (u32, u32),
+        piece_width: f32,
+        piece_height: f32,
+        wrap_around: Option<&WrapAround>,
+    ) -> JigsawResult<Vec<JigsawPiece>> {
+        let mut owners: HashMap<_, Vec<(usize, usize)>> = HashMap::new();
+        // the polylines of the edges at their place in the image, which differs between the
+        // copies of an edge on opposite borders
+        let mut polylines: HashMap<_, Vec<DVec2>> = HashMap::new();
+        for (i, piece) in pieces.iter().enumerate() {
+            for (j, edge) in piece.edges.iter().enumerate() {
+                owners
+                    .entry(shared_edge_key(edge, wrap_around))
+                    .or_default()
+                    .push((i, j));
+                polylines
+                    .entry(edge_key(edge))
+                    .or_insert_with(|| flatten(edge));
+            }
+        }
+
+        let mut contour_gen = self.reroll_generator(piece_width, piece_height)?;
+        let mut changed = vec![false; pieces.len()];
+        // an edge which became straight may still collide with an edge checked before it, so the
+        // pieces with replaced edges are checked again in the next pass
+        let mut dirty = vec![true; pieces.len()];
+        for _ in 0..MAX_PASSES {
+            let mut replaced = vec![false; pieces.len()];
+            for i in 0..pieces.len() {
+                for j in 0..pieces[i].edges.len() {
+                    let key = shared_edge_key(&pieces[i].edges[j], wrap_around);
+                    // every shared edge is checked only once, by the first piece it belongs to
+                    if owners[&key][0] != (i, j)
+                        || !matches!(pieces[i].edges[j], Edge::IndentedEdge(_))
+                        || !owners[&key].iter().any(|&(piece, _)| dirty[piece])
+                    {
+                        continue;
+                    }
+                    let (starting_point, end_point) = (
+                        pieces[i].edges[j].starting_point(),
+                        pieces[i].edges[j].end_point(),
+                    );
+                    let mut attempt = 0;
+                    while collides(&pieces, &owners[&key], &polylines) {
+                        debug!("replacing colliding edge {j} of piece {i}, attempt {attempt}");
+                        let edge = if attempt < MAX_REROLLS + MAX_SHRINKS {
+                            let shrinks = attempt.saturating_sub(MAX_REROLLS) as i32;
+                            contour_gen.tab_scale = SHRINK_FACTOR.powi(shrinks);
+                            let edge = match game_mode {
+                                GameMode::Classic => {
+                                    lattice_edge(starting_point, end_point, &mut contour_gen)?
+                                }
+                                _ => IndentedEdge::new_along(
+                                    starting_point,
+                                    end_point,
+                                    &mut contour_gen,
+                                )?,
+                            };
+                            contour_gen.tab_scale = 1.0;
+                            match wrap_around {
+                                Some(_) => Edge::IndentedEdge(edge).snapped(),
+                                None => Edge::IndentedEdge(edge),
+                            }
+                        } else {
+                            Edge::StraightEdge(StraightEdge {
+                                starting_point,
+                                end_point,
+                            })
+                        };
+                        for &(piece, index) in owners[&key].iter() {
+                            let owned = &pieces[piece].edges[index];
+                            let (x, y) = owned.starting_point();
+                            let edge = edge.translated(x - starting_point.0, y - starting_point.1);
+                            polylines.insert(edge_key(&edge), flatten(&edge));
+                            pieces[piece].edges[index] = edge;
+                            changed[piece] = true;
+                            replaced[piece] = true;
+                        }
+                        if matches!(edge, Edge::StraightEdge(_)) {
+                            break;
+                        }
+                        attempt += 1;
+                    }
+                }
+            }
+            if !replaced.contains(&true) {
+                break;
+            }
+            dirty = replaced;
+        }
+
+        for (piece, changed) in pieces.iter_mut().zip(changed) {
+            if changed {
+                *piece = JigsawPiece::new(
+                    piece.index,
+                    piece.start_point,
+                    image_size,
+                    (piece.width, piece.height),
+                    std::mem::take(&mut piece.edges),
+                    piece.is_boarder,
+                )?;
+            }
+        }
+        Ok(pieces)
+    }
+
+    /// Returns a generator for replacement edges. It has the tab settings of the generator, but
+    /// an independent sequence of random values, so the other edges stay the same.
+    fn reroll_generator(
+        &self,
+        piece_width: f32,
+        piece_height: f32,
+    ) -> JigsawResult<EdgeContourGenerator> {
+        let mut contour_gen = self.contour_generator(piece_width, piece_height)?;
+        let counter = EdgeContourGenerator::mix(self.seed.unwrap_or(0) as u64).rotate_left(16);
+        (
+            contour_gen.seed,
+            contour_gen.flipped,
+            contour_gen.a,
+            contour_gen.b,
+            contour_gen.c,
+            contour_gen.d,
+            contour_gen.e,
+        ) = EdgeContourGenerator::dice(contour_gen.e, false, counter, contour_gen.jitter);
+        Ok(contour_gen)
+    }
+}
+
+fn flatten(edge: &Edge) -> Vec<DVec2> {
+    raster::flatten_open(&edge.to_beziers(false))
+}
+
+/// Returns whether the edge at the given positions intersects itself or another edge of one of
+/// the pieces it belongs to
+fn collides(
+    pieces: &[JigsawPiece],
+    positions: &[(usize, usize)],
+    polylines: &HashMap<EdgeKey, Vec<DVec2>>,
+) -> bool {
+    let (i, j) = positions[0];
+    if self_intersects(&polylines[&edge_key(&pieces[i].edges[j])]) {
+        return true;
+    }
+    positions.iter().any(|&(piece, index)| {
+        let key = edge_key(&pieces[piece].edges[index]);
+        let polyline = &polylines[&key];
+        pieces[piece]
+            .edges
+            .iter()
+            .map(edge_key)
+            .filter(|other| *other != key)
+            .any(|other| polylines_intersect(polyline, &polylines[&other]))
+    })
+}
+
+/// A segment of a polyline with its position in the polyline
+type Segment = (usize, DVec2, DVec2);
+
+fn segments(polyline: &[DVec2]) -> Vec<Segment> {
+    polyline
+        .windows(2)
+        .enumerate()
+        .map(|(i, points)| (i, points[0], points[1]))
+        .collect()
+}
+
+/// Returns whether two non-adjacent segments of the polyline intersect
+fn self_intersects(polyline: &[DVec2]) -> bool {
+    let segments = segments(polyline);
+    sweep(&segments, &segments, |&(i, a0, a1), &(j, b0, b1)| {
+        i + 1 < j && intersection(a0, a1, b0, b1).is_some()
+    })
+}
+
+/// Returns whether the polylines intersect anywhere but in a shared end point
+fn polylines_intersect(a: &[DVec2], b: &[DVec2]) -> bool {
+    let (box_a, box_b) = (bounding_box(a), bounding_box(b));
+    if box_a.0.cmpgt(box_b.1).any() || box_b.0.cmpgt(box_a.1).any() {
+        return false;
+    }
+    let corners: Vec<DVec2> = [a[0], a[a.len() - 1]]
+        .into_iter()
+        .filter(|corner| [b[0], b[b.len() - 1]].contains(corner))
+        .collect();
+    // only the segments within the bounding box of the other polyline may intersect it
+    let within = |(min, max): (DVec2, DVec2)| {
+        move |&(_, p, q): &Segment| p.min(q).cmple(max).all() && p.max(q).cmpge(min).all()
+    };
+    let a: Vec<Segment> = segments(a).into_iter().filter(within(box_b)).collect();
+    let b: Vec<Segment> = segments(b).into_iter().filter(within(box_a)).collect();
+    sweep(&a, &b, |&(_, a0, a1), &(_, b0, b1)| {
+        intersection(a0, a1, b0, b1).is_some_and(|point| {
+            !corners
+                .iter()
+                .any(|corner| corner.distance(point) < CORNER_TOLERANCE)
+        })
+    })
+}
+
+fn bounding_box(points: &[DVec2]) -> (DVec2, DVec2) {
+    points.iter().fold(
+        (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY)),
+        |(min, max), point| (min.min(*point), max.max(*point)),
+    )
+}
+
+/// Calls `test` for the pairs of a segment from `a` and one from `b` whose extents overlap on
+/// the axis along which the segments spread the most, until it returns `true`
+fn sweep(a: &[Segment], b: &[Segment], mut test: impl FnMut(&Segment, &Segment) -> bool) -> bool {
+    let points = a.iter().chain(b.iter()).flat_map(|&(_, p, q)| [p, q]);
+    let (min, max) = points.fold(
+        (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY)),
+        |(min, max), point| (min.min(point), max.max(point)),
+    );
+    let axis = if max.x - min.x >= max.y - min.y { 0 } else { 1 };
+    let extent = |&(_, p, q): &Segment| (p[axis].min(q[axis]), p[axis].max(q[axis]));
+
+    let mut events: Vec<(bool, &Segment)> = a
+        .iter()
+        .map(|segment| (true, segment))
+        .chain(b.iter().map(|segment| (false, segment)))
+        .collect();
+    events.sort_by(|x, y| extent(x.1).0.total_cmp(&extent(y.1).0));
+    let (mut active_a, mut active_b): (Vec<&Segment>, Vec<&Segment>) = (vec![], vec![]);
+    for (in_a, segment) in events {
+        let start = extent(segment).0;
+        let (own, others) = if in_a {
+            (&mut active_a, &mut active_b)
+        } else {
+            (&mut active_b, &mut active_a)
+        };
+        others.retain(|other| extent(other).1 >= start);
+        let found = others.iter().any(|other| {
+            if in_a {
+                test(segment, other)
+            } else {
+                test(other, segment)
+            }
+        });
+        if found {
+            return true;
+        }
+        own.push(segment);
+    }
+    false
+}
+
+/// Returns a point the two segments have in common, if any
+fn intersection(a0: DVec2, a1: DVec2, b0: DVec2, b1: DVec2) -> Option<DVec2> {
+    let (da, db) = (a1 - a0, b1 - b0);
+    let d1 = db.perp_dot(a0 - b0);
+    let d2 = db.perp_dot(a1 - b0);
+    let d3 = da.perp_dot(b0 - a0);
+    let d4 = da.perp_dot(b1 - a0);
+    if d1 * d2 > 0.0 || d3 * d4 > 0.0 {
+        return None;
+    }
+    if d1 != d2 {
+        return Some(a0 + da * (d1 / (d1 - d2)));
+    }
+    // collinear segments, return the middle of their overlap
+    let direction = if da.length_squared() > 0.0 { da } else { db };
+    if direction.length_squared() == 0.0 {
+        return (a0 == b0).then_some(a0);
+    }
+    let project = |point: DVec2| (point - a0).dot(direction) / direction.length_squared();
+    let (min_a, max_a) = (project(a0).min(project(a1)), project(a0).max(project(a1)));
+    let (min_b, max_b) = (project(b0).min(project(b1)), project(b0).max(project(b1)));
+    let (start, end) = (min_a.max(min_b), max_a.min(max_b));
+    (start <= end).then(|| a0 + direction * ((start + end) / 2.0))
+}
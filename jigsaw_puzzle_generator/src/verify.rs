@@ -0,0 +1,179 @@
+//! Consistency checks of generated templates
+
+use crate::label::{LabelMap, NO_PIECE, OVERLAPPING_PIECES};
+use crate::wrap::shared_edge_key;
+use crate::{raster, Edge, JigsawError, JigsawResult, JigsawTemplate};
+use glam::DVec2;
+use image::GenericImageView;
+use std::collections::HashMap;
+
+/// Maximum distance in pixels of a border edge from the border of the image
+const BORDER_TOLERANCE: f64 = 0.01;
+
+/// Distance in pixels from the middle of an edge at which its two sides are sampled
+const SIDE_DISTANCE: f64 = 2.0;
+
+impl JigsawTemplate {
+    /// Checks that the pieces tile the image: every pixel is covered by exactly one piece, the
+    /// crop rectangle of every piece contains all of its pixels, and every edge is either shared
+    /// by exactly two pieces or lies on the border of the image.
+    ///
+    /// If the pieces follow the silhouette of the image, see [`JigsawTemplate::alpha_threshold`],
+    /// the transparent pixels may be uncovered, and pieces may have edges without a neighbour
+    /// inside of the image if the neighbour was dropped, i.e. no piece covers the other side of
+    /// the edge and it is transparent.
+    ///
+    /// If the puzzle wraps around, see [`JigsawTemplate::wrap_around`], there is no border: every
+    /// edge is shared, and the pixels of the margin count for the pixels of the puzzle they show.
+    ///
+    /// Returns the first violation found. Self-intersecting tabs show up as uncovered or
+    /// overlapping pixels.
+    pub fn verify(&self) -> JigsawResult<()> {
+        let (width, height) = self.image_dimensions();
+        let silhouette = self.alpha_threshold.is_some();
+        let wrap_around = self.wrap_around.as_ref();
+        let edge_key = |edge: &Edge| shared_edge_key(edge, wrap_around);
+
+        let labels = self.label_map();
+        let mut owners: HashMap<_, Vec<usize>> = HashMap::new();
+        for piece in self.pieces.iter() {
+            for edge in piece.edges.iter() {
+                owners.entry(edge_key(edge)).or_default().push(piece.index);
+            }
+        }
+        for piece in self.pieces.iter() {
+            for edge in piece.edges.iter() {
+                let count = owners[&edge_key(edge)].len();
+                let outside = (wrap_around.is_none() && on_border(edge, width, height))
+                    || (silhouette && self.borders_dropped_piece(edge, piece.index, &labels));
+                if count > 2 || (count == 1 && !outside) {
+                    return Err(JigsawError::UnsharedEdge {
+                        index: piece.index,
+                        owners: count,
+                    });
+                }
+            }
+        }
+
+        let mut uncovered = vec![];
+        let mut overlapping = vec![];
+        // how many pieces cover every pixel of a puzzle which wraps around
+        let mut coverage = match wrap_around {
+            Some(wrap_around) => vec![0u8; (wrap_around.width * wrap_around.height) as usize],
+            None => vec![],
+        };
+        for (x, y, label) in labels.enumerate_pixels() {
+            match label.0[0] {
+                NO_PIECE if self.is_background(x, y) || wrap_around.is_some() => {}
+                NO_PIECE => uncovered.push((x, y)),
+                OVERLAPPING_PIECES => overlapping.push((x, y)),
+                index => {
+                    if let Some(wrap_around) = wrap_around {
+                        let (x, y) = wrap_around.torus_pixel(x, y);
+                        let count = &mut coverage[(y * wrap_around.width + x) as usize];
+                        *count = count.saturating_add(1);
+                    }
+                    let piece = &self.pieces[index as usize];
+                    if !(piece.top_left_x..piece.top_left_x + piece.crop_width).contains(&x)
+                        || !(piece.top_left_y..piece.top_left_y + piece.crop_height).contains(&y)
+                    {
+                        return Err(JigsawError::ClippedPiece {
+                            index: piece.index,
+                            x,
+                            y,
+                        });
+                    }
+                }
+            }
+        }
+        if let Some(wrap_around) = wrap_around {
+            for (i, &count) in coverage.iter().enumerate() {
+                let (x, y) = (
+                    i as u32 % wrap_around.width + wrap_around.margin,
+                    i as u32 / wrap_around.width + wrap_around.margin,
+                );
+                match count {
+                    0 if self.is_background(x, y) => {}
+                    0 => uncovered.push((x, y)),
+                    1 => {}
+                    _ => overlapping.push((x, y)),
+                }
+            }
+        }
+        if let Some(&(x, y)) = uncovered.first() {
+            return Err(JigsawError::UncoveredPixels {
+                count: uncovered.len(),
+                x,
+                y,
+            });
+        }
+        if let Some(&(x, y)) = overlapping.first() {
+            return Err(JigsawError::OverlappingPixels {
+                count: overlapping.len(),
+                x,
+                y,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns whether the edge of the piece borders a piece which was dropped for covering only
+    /// transparent pixels. The two sides of the middle of the edge are sampled: one has to be the
+    /// piece, the other one background which no piece covers.
+    fn borders_dropped_piece(&self, edge: &Edge, index: usize, labels: &LabelMap) -> bool {
+        let points = raster::flatten_open(&edge.to_beziers(false));
+        if points.len() < 2 {
+            return false;
+        }
+        let middle = (points.len() / 2).max(1);
+        let normal = (points[middle] - points[middle - 1])
+            .perp()
+            .normalize_or_zero();
+        let (x, y) = (points[middle].x, points[middle].y);
+        let label = |offset: f64| {
+            let (x, y) = (x + normal.x * offset, y + normal.y * offset);
+            if x < 0.0 || y < 0.0 || x >= labels.width() as f64 || y >= labels.height() as f64 {
+                return None;
+            }
+            Some((
+                x as u32,
+                y as u32,
+                labels.get_pixel(x as u32, y as u32).0[0],
+            ))
+        };
+        let is_dropped = |side: Option<(u32, u32, u32)>| match side {
+            None => true,
+            Some((x, y, label)) => label == NO_PIECE && self.is_background(x, y),
+        };
+        let (left, right) = (label(SIDE_DISTANCE), label(-SIDE_DISTANCE));
+        let is_piece = |side: Option<(u32, u32, u32)>| {
+            side.is_some_and(|(_, _, label)| label as usize == index)
+        };
+        (is_piece(left) && is_dropped(right)) || (is_piece(right) && is_dropped(left))
+    }
+
+    /// Returns whether the pixel is transparent background of a template which follows the
+    /// silhouette of its image. Without the image every pixel may be background.
+    fn is_background(&self, x: u32, y: u32) -> bool {
+        let Some(threshold) = self.alpha_threshold else {
+            return false;
+        };
+        let (width, height) = self.origin_image.dimensions();
+        x >= width || y >= height || self.origin_image.get_pixel(x, y).0[3] <= threshold
+    }
+}
+
+/// Returns whether the whole edge lies on one side of the border of the image
+fn on_border(edge: &Edge, width: u32, height: u32) -> bool {
+    let points = raster::flatten_open(&edge.to_beziers(false));
+    let (width, height) = (width as f64, height as f64);
+    let on_line = |coordinate: fn(&DVec2) -> f64, line: f64| {
+        points
+            .iter()
+            .all(|point| (coordinate(point) - line).abs() < BORDER_TOLERANCE)
+    };
+    on_line(|point| point.x, 0.0)
+        || on_line(|point| point.x, width)
+        || on_line(|point| point.y, 0.0)
+        || on_line(|point| point.y, height)
+}
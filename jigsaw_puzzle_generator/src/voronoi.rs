@@ -0,0 +1,232 @@
+//! Generator for [`GameMode::Irregular`](crate::GameMode::Irregular)
+//!
+//! Every cell of the regular grid gets one seed point, which is shifted by a random offset. The
+//! seed points are moved towards the centroids of their Voronoi cells a few times (Lloyd's
+//! relaxation) to even out the piece sizes, and the final Voronoi cells become the pieces. Each
+//! cell is computed by clipping the image rectangle with the bisectors to the other seed points,
+//! so every side of a cell knows whether it lies on the image border or which cell it borders.
+
+use crate::{
+    round, Edge, IndentedEdge, JigsawError, JigsawGenerator, JigsawPiece, JigsawResult,
+    StraightEdge,
+};
+use glam::DVec2;
+use log::debug;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+/// How many times the seed points are moved to the centroids of their cells
+const LLOYD_ITERATIONS: usize = 2;
+/// Maximum offset of a seed point from the centre of its grid cell, relative to the piece size
+const SITE_JITTER: f32 = 0.35;
+/// Cell corners closer to each other than this many pixels are merged into one
+const VERTEX_TOLERANCE: f64 = 0.5;
+
+/// What lies on the other side of a cell's side
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Border {
+    Image,
+    Cell(usize),
+}
+
+/// A convex cell as a list of corners, each with the border of the side to the next corner
+type Cell = Vec<(DVec2, Border)>;
+
+impl JigsawGenerator {
+    pub(crate) fn voronoi_generator(
+        &self,
+        image_size: (u32, u32),
+        piece_width: f32,
+        piece_height: f32,
+    ) -> JigsawResult<Vec<JigsawPiece>> {
+        let image_width = image_size.0 as f64;
+        let image_height = image_size.1 as f64;
+        let mut contour_gen = self.contour_generator(piece_width, piece_height)?;
+
+        let mut random = self.layout_random();
+        let mut jitter = |size: f32| (random(-SITE_JITTER, SITE_JITTER) * size) as f64;
+        let mut sites = vec![];
+        for row in 0..self.pieces_in_row {
+            for column in 0..self.pieces_in_column {
+                let x = (column as f64 + 0.5) * piece_width as f64 + jitter(piece_width);
+                let y = (row as f64 + 0.5) * piece_height as f64 + jitter(piece_height);
+                sites.push(DVec2::new(x, y));
+            }
+        }
+
+        for _ in 0..LLOYD_ITERATIONS {
+            sites = voronoi_cells(&sites, image_width, image_height)
+                .iter()
+                .zip(sites.iter())
+                .map(|(cell, site)| centroid(cell).unwrap_or(*site))
+                .collect();
+        }
+        let cells = voronoi_cells(&sites, image_width, image_height);
+
+        // the corners shared by neighbouring cells are computed once for each of them, so they
+        // are merged to get exactly matching edges
+        let mut vertices: Vec<(f32, f32)> = vec![];
+        let mut vertex_id = |point: DVec2| {
+            let point = DVec2::new(snap(point.x, image_width), snap(point.y, image_height));
+            match vertices.iter().position(|&(x, y)| {
+                DVec2::new(x as f64, y as f64).distance(point) < VERTEX_TOLERANCE
+            }) {
+                Some(id) => id,
+                None => {
+                    vertices.push((round(point.x as f32), round(point.y as f32)));
+                    vertices.len() - 1
+                }
+            }
+        };
+        let cells: Vec<Vec<(usize, Border)>> = cells
+            .iter()
+            .map(|cell| {
+                let mut corners: Vec<(usize, Border)> = vec![];
+                for &(point, border) in cell.iter() {
+                    let id = vertex_id(point);
+                    // a side shorter than the tolerance collapses into a single corner
+                    if corners.last().is_some_and(|&(last, _)| last == id) {
+                        corners.pop();
+                    }
+                    corners.push((id, border));
+                }
+                if corners.len() > 1 && corners.first().map(|c| c.0) == corners.last().map(|c| c.0)
+                {
+                    corners.pop();
+                }
+                corners
+            })
+            .collect();
+
+        // sides shorter than a third of a piece are too short for a tab
+        let min_tab_length = piece_width.min(piece_height) / 3.0;
+        let mut edges: HashMap<(usize, usize), Edge> = HashMap::new();
+        let mut pieces = vec![];
+        for (index, corners) in cells.iter().enumerate() {
+            debug!("starting process irregular piece {index}");
+            if corners.len() < 3 {
+                return Err(JigsawError::DegeneratedCell(index));
+            }
+            let mut piece_edges = vec![];
+            let mut is_boarder = false;
+            for (i, &(start, border)) in corners.iter().enumerate() {
+                let end = corners[(i + 1) % corners.len()].0;
+                let (starting_point, end_point) = (vertices[start], vertices[end]);
+                let length = ((end_point.0 - starting_point.0).powi(2)
+                    + (end_point.1 - starting_point.1).powi(2))
+                .sqrt();
+                is_boarder |= border == Border::Image;
+                let edge = match edges.entry((start.min(end), start.max(end))) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        entry.insert(if border == Border::Image || length < min_tab_length {
+                            Edge::StraightEdge(StraightEdge {
+                                starting_point,
+                                end_point,
+                            })
+                        } else {
+                            Edge::IndentedEdge(IndentedEdge::new_along(
+                                starting_point,
+                                end_point,
+                                &mut contour_gen,
+                            )?)
+                        })
+                    }
+                };
+                piece_edges.push(edge.clone());
+            }
+
+            let xs = corners.iter().map(|&(id, _)| vertices[id].0);
+            let ys = corners.iter().map(|&(id, _)| vertices[id].1);
+            let (min_x, max_x) = xs.fold((f32::MAX, f32::MIN), |(a, b), x| (a.min(x), b.max(x)));
+            let (min_y, max_y) = ys.fold((f32::MAX, f32::MIN), |(a, b), y| (a.min(y), b.max(y)));
+            pieces.push(JigsawPiece::new(
+                index,
+                (min_x, min_y),
+                image_size,
+                (max_x - min_x, max_y - min_y),
+                piece_edges,
+                is_boarder,
+            )?);
+        }
+
+        Ok(pieces)
+    }
+}
+
+/// Returns the Voronoi cell of every site within the image rectangle
+fn voronoi_cells(sites: &[DVec2], image_width: f64, image_height: f64) -> Vec<Cell> {
+    sites
+        .iter()
+        .enumerate()
+        .map(|(i, &site)| {
+            let mut cell = vec![
+                (DVec2::new(0.0, 0.0), Border::Image),
+                (DVec2::new(image_width, 0.0), Border::Image),
+                (DVec2::new(image_width, image_height), Border::Image),
+                (DVec2::new(0.0, image_height), Border::Image),
+            ];
+            let mut others: Vec<usize> = (0..sites.len()).filter(|&j| j != i).collect();
+            others.sort_by(|&a, &b| site.distance(sites[a]).total_cmp(&site.distance(sites[b])));
+            for j in others {
+                // a bisector further away than the farthest corner can't cut the cell anymore
+                let radius = cell
+                    .iter()
+                    .map(|(point, _)| site.distance(*point))
+                    .fold(0.0, f64::max);
+                if site.distance(sites[j]) > 2.0 * radius {
+                    break;
+                }
+                cell = clip(&cell, site, sites[j], j);
+            }
+            cell
+        })
+        .collect()
+}
+
+/// Cuts off the part of the cell which is closer to the `other` site than to the cell's `site`
+fn clip(cell: &Cell, site: DVec2, other: DVec2, other_index: usize) -> Cell {
+    let middle = (site + other) / 2.0;
+    let direction = other - site;
+    let distance = |point: DVec2| (point - middle).dot(direction);
+    let mut clipped = vec![];
+    for (i, &(start, border)) in cell.iter().enumerate() {
+        let end = cell[(i + 1) % cell.len()].0;
+        let (d_start, d_end) = (distance(start), distance(end));
+        let intersection = || start + (end - start) * (d_start / (d_start - d_end));
+        match (d_start <= 0.0, d_end <= 0.0) {
+            (true, true) => clipped.push((start, border)),
+            (true, false) => {
+                clipped.push((start, border));
+                clipped.push((intersection(), Border::Cell(other_index)));
+            }
+            (false, true) => clipped.push((intersection(), border)),
+            (false, false) => {}
+        }
+    }
+    clipped
+}
+
+/// Returns the centroid of a cell, or `None` if it has no area
+fn centroid(cell: &Cell) -> Option<DVec2> {
+    let mut area = 0.0;
+    let mut sum = DVec2::ZERO;
+    for (i, &(start, _)) in cell.iter().enumerate() {
+        let end = cell[(i + 1) % cell.len()].0;
+        let cross = start.perp_dot(end);
+        area += cross;
+        sum += (start + end) * cross;
+    }
+    (area.abs() > f64::EPSILON).then(|| sum / (3.0 * area))
+}
+
+/// Moves a coordinate which is almost on the image border exactly onto it
+fn snap(value: f64, max: f64) -> f64 {
+    if value.abs() < VERTEX_TOLERANCE {
+        0.0
+    } else if (value - max).abs() < VERTEX_TOLERANCE {
+        max
+    } else {
+        value
+    }
+}
@@ -0,0 +1,217 @@
+//! Puzzles which wrap around at the borders of the image
+//!
+//! The edges on the left and top border of the image get tabs like the inner edges, and the
+//! edges on the right and bottom border are copies of them moved by the width or the height of
+//! the image. So every piece has neighbours on all sides and the puzzle has no border at all.
+//!
+//! The tabs of these edges reach beyond the image. The template image is therefore padded with a
+//! margin on every side which repeats the opposite side of the image, and all edges are moved by
+//! the margin. The points of the edges are rounded to a fine grid, so the copies of an edge on
+//! opposite borders are exact and identified as the same edge.
+
+use crate::svg::{edge_key, EdgeKey};
+use crate::{Edge, JigsawPiece, JigsawResult, StraightEdge};
+use image::{DynamicImage, GenericImage, GenericImageView};
+use log::debug;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Subdivisions of a pixel the points of a puzzle which wraps around are rounded to
+const GRID: f32 = 64.0;
+/// Maximum distance in pixels of a corner from the right or bottom border of the puzzle at which
+/// it counts as the copy of a corner on the left or top border
+const BORDER_TOLERANCE: f32 = 0.5;
+
+/// Where the puzzle of a template which wraps around lies in the template image, see
+/// [`crate::JigsawGenerator::wrap_around`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WrapAround {
+    /// Width of the margin around the puzzle in pixel, which repeats the opposite side of it
+    pub margin: u32,
+    /// Width of the puzzle in pixel, after which the pieces repeat along the x-axis
+    pub width: u32,
+    /// Height of the puzzle in pixel, after which the pieces repeat along the y-axis
+    pub height: u32,
+}
+
+impl WrapAround {
+    /// Returns the size of the template image, the puzzle with the margin on every side
+    pub(crate) fn image_size(&self) -> (u32, u32) {
+        (self.width + 2 * self.margin, self.height + 2 * self.margin)
+    }
+
+    /// Returns the image padded with the margin
+    pub(crate) fn pad_image(&self, image: &DynamicImage) -> DynamicImage {
+        let (width, height) = self.image_size();
+        let mut padded = DynamicImage::new_rgba8(width, height);
+        for y in 0..padded.height() {
+            for x in 0..padded.width() {
+                let (x_in, y_in) = self.torus_pixel(x, y);
+                padded.put_pixel(x, y, image.get_pixel(x_in, y_in));
+            }
+        }
+        padded
+    }
+
+    /// Returns the pixel of the puzzle, without the margin, which is shown at the given pixel of
+    /// the template image
+    pub(crate) fn torus_pixel(&self, x: u32, y: u32) -> (u32, u32) {
+        (
+            (x as i64 - self.margin as i64).rem_euclid(self.width as i64) as u32,
+            (y as i64 - self.margin as i64).rem_euclid(self.height as i64) as u32,
+        )
+    }
+
+    /// Identifies an edge like [`edge_key`], but the copies on the right and bottom border of
+    /// the puzzle are moved back onto the left and top border
+    pub(crate) fn edge_key(&self, edge: &Edge) -> EdgeKey {
+        let (start, end) = (edge.starting_point(), edge.end_point());
+        let right = (self.margin + self.width) as f32 - BORDER_TOLERANCE;
+        let bottom = (self.margin + self.height) as f32 - BORDER_TOLERANCE;
+        let dx = if start.0 >= right && end.0 >= right {
+            self.width as f32
+        } else {
+            0.0
+        };
+        let dy = if start.1 >= bottom && end.1 >= bottom {
+            self.height as f32
+        } else {
+            0.0
+        };
+        edge_key(&Edge::StraightEdge(StraightEdge {
+            starting_point: (start.0 - dx, start.1 - dy),
+            end_point: (end.0 - dx, end.1 - dy),
+        }))
+    }
+
+    /// Maps a point of the template onto the template scaled to `scaled`, so that the copies of
+    /// a point on opposite borders stay copies of each other exactly
+    pub(crate) fn scale_point(&self, scaled: &WrapAround, (x, y): (f32, f32)) -> (f32, f32) {
+        let scale_axis = |value: f32, margin: u32, length: u32, new_margin: u32, new_length| {
+            let value = value - margin as f32;
+            let period = ((value + BORDER_TOLERANCE) / length as f32).floor();
+            let rest = value - period * length as f32;
+            snap_to_grid(rest * new_length as f32 / length as f32)
+                + (new_margin as f32 + period * new_length as f32)
+        };
+        (
+            scale_axis(x, self.margin, self.width, scaled.margin, scaled.width),
+            scale_axis(y, self.margin, self.height, scaled.margin, scaled.height),
+        )
+    }
+}
+
+/// Rounds a coordinate to a multiple of 1/64 pixel. Sums of such coordinates and whole pixels are
+/// exact, so an edge moved by the size of the puzzle ends exactly at the copies of its corners.
+pub(crate) fn snap_to_grid(value: f32) -> f32 {
+    (value * GRID).round() / GRID
+}
+
+/// Returns the margin which holds the parts of the edges reaching beyond the puzzle, which lies
+/// at `offset` in the image
+fn margin(edges: impl Iterator<Item = Edge>, offset: f32, width: u32, height: u32) -> u32 {
+    let offset = offset as f64;
+    let protrusion = edges
+        .flat_map(|edge| edge.to_beziers(false))
+        .map(|bezier| {
+            let [min, max] = bezier.bounding_box();
+            (offset - min.x)
+                .max(offset - min.y)
+                .max(max.x - offset - width as f64)
+                .max(max.y - offset - height as f64)
+        })
+        .fold(0.0, f64::max);
+    protrusion.ceil() as u32
+}
+
+/// Returns where the puzzle lies in the padded image, and moves the corners and edges of the
+/// layout by the margin
+pub(crate) fn pad_layout(
+    (width, height): (u32, u32),
+    corners: &mut [Vec<(f32, f32)>],
+    vertical_edges: &mut [Edge],
+    horizontal_edges: &mut [Edge],
+) -> WrapAround {
+    let edges = vertical_edges
+        .iter()
+        .chain(horizontal_edges.iter())
+        .cloned();
+    let wrap_around = WrapAround {
+        margin: margin(edges, 0.0, width, height),
+        width,
+        height,
+    };
+
+    let margin = wrap_around.margin as f32;
+    for corner in corners.iter_mut().flatten() {
+        *corner = (corner.0 + margin, corner.1 + margin);
+    }
+    for edge in vertical_edges.iter_mut().chain(horizontal_edges.iter_mut()) {
+        *edge = edge.translated(margin, margin);
+    }
+    wrap_around
+}
+
+/// Widens the margin if the edges of the pieces reach beyond it, and moves the pieces alike
+pub(crate) fn fit_margin(
+    pieces: Vec<JigsawPiece>,
+    wrap_around: WrapAround,
+) -> JigsawResult<(Vec<JigsawPiece>, WrapAround)> {
+    let edges = pieces.iter().flat_map(|piece| piece.edges.iter()).cloned();
+    let margin = margin(
+        edges,
+        wrap_around.margin as f32,
+        wrap_around.width,
+        wrap_around.height,
+    );
+    if margin == 0 {
+        return Ok((pieces, wrap_around));
+    }
+    debug!("widening the margin by {margin} pixels");
+    let widened = WrapAround {
+        margin: wrap_around.margin + margin,
+        ..wrap_around
+    };
+    let shift = margin as f32;
+    let pieces = pieces
+        .into_iter()
+        .map(|piece| {
+            JigsawPiece::new(
+                piece.index,
+                (piece.start_point.0 + shift, piece.start_point.1 + shift),
+                widened.image_size(),
+                (piece.width, piece.height),
+                piece
+                    .edges
+                    .iter()
+                    .map(|edge| edge.translated(shift, shift))
+                    .collect(),
+                piece.is_boarder,
+            )
+        })
+        .collect::<JigsawResult<Vec<_>>>()?;
+    Ok((pieces, widened))
+}
+
+/// Identifies an edge regardless of its direction, and of the border of the puzzle it is on if
+/// it wraps around
+pub(crate) fn shared_edge_key(edge: &Edge, wrap_around: Option<&WrapAround>) -> EdgeKey {
+    match wrap_around {
+        Some(wrap_around) => wrap_around.edge_key(edge),
+        None => edge_key(edge),
+    }
+}
+
+impl Edge {
+    /// Returns the edge moved by `dx` along the x-axis and by `dy` along the y-axis
+    pub(crate) fn translated(&self, dx: f32, dy: f32) -> Edge {
+        self.map_points(|(x, y)| (x + dx, y + dy))
+    }
+
+    /// Returns the edge with all points rounded to the grid of [`snap_to_grid`], so its copies
+    /// moved by whole pixels are exact
+    pub(crate) fn snapped(&self) -> Edge {
+        self.map_points(|(x, y)| (snap_to_grid(x), snap_to_grid(y)))
+    }
+}
@@ -1,6 +1,6 @@
 use crate::NORMAL_BUTTON;
 use crate::{despawn_screen, GameState};
-use crate::{AppState, OriginImage, Piece, SelectGameMode, SelectPiece};
+use crate::{AppState, GenerateError, OriginImage, Piece, SelectGameMode, SelectPiece};
 use bevy::asset::RenderAssetUsages;
 use bevy::color::palettes::basic::{GREEN, YELLOW};
 use bevy::ecs::world::CommandQueue;
@@ -12,9 +12,12 @@ use bevy::time::Stopwatch;
 use bevy::utils::HashSet;
 use bevy::window::WindowMode;
 use core::ops::DerefMut;
-use jigsaw_puzzle_generator::image::GenericImageView;
-use jigsaw_puzzle_generator::{JigsawGenerator, JigsawPiece, JigsawTemplate};
-use log::debug;
+use jigsaw_puzzle_generator::image::{DynamicImage, GenericImageView};
+use jigsaw_puzzle_generator::{
+    AtlasOptions, BevelOptions, CropOptions, JigsawGenerator, JigsawPiece, JigsawResult,
+    JigsawTemplate,
+};
+use log::{debug, error};
 use rand::Rng;
 
 pub(super) fn plugin(app: &mut App) {
@@ -32,7 +35,7 @@ pub(super) fn plugin(app: &mut App) {
     // generation piece
     app.add_systems(
         OnEnter(GameState::Generating),
-        (setup_generator, setup_generating_ui, spawn_piece).chain(),
+        (setup_generator, spawn_piece, setup_generating_ui).chain(),
     )
     .add_systems(
         OnExit(GameState::Generating),
@@ -41,8 +44,10 @@ pub(super) fn plugin(app: &mut App) {
     .add_systems(Update, (adjust_camera_on_added_sprite,))
     .add_systems(
         PostUpdate,
-        (handle_tasks, count_spawned_piece).run_if(in_state(GameState::Generating)),
-    );
+        count_spawned_piece.run_if(in_state(GameState::Generating)),
+    )
+    // the levels of detail are also cropped while playing
+    .add_systems(PostUpdate, handle_tasks);
 
     // pause logic
     app.add_systems(OnEnter(GameState::Pause), setup_pause_ui)
@@ -64,6 +69,7 @@ pub(super) fn plugin(app: &mut App) {
                 cancel_all_move,
                 shuffle_pieces,
                 adjust_camera_scale,
+                swap_piece_lod,
                 handle_keyboard_input,
                 handle_mouse_wheel_input,
                 handle_toggle_background_hint,
@@ -227,18 +233,67 @@ impl core::fmt::Display for GameTimer {
     }
 }
 
+/// Goes back to the main menu, which shows the message, and drops the entities and resources of
+/// the puzzle so the systems of the generating state don't pick them up
+fn abort_generating(
+    commands: &mut Commands,
+    app_state: &mut NextState<AppState>,
+    play_screen: &Query<Entity, With<OnPlayScreen>>,
+    message: String,
+) {
+    error!("{message}");
+    for entity in play_screen.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<JigsawPuzzleGenerator>();
+    commands.remove_resource::<JigsawPuzzleTemplate>();
+    commands.remove_resource::<PieceLods>();
+    commands.insert_resource(GenerateError(message));
+    app_state.set(AppState::MainMenu);
+}
+
 fn setup_generator(
     mut commands: Commands,
+    mut app_state: ResMut<NextState<AppState>>,
+    play_screen: Query<Entity, With<OnPlayScreen>>,
     images: Res<Assets<Image>>,
     origin_image: Res<OriginImage>,
     select_piece: Res<SelectPiece>,
+    select_game_mode: Res<SelectGameMode>,
 ) {
-    let image = images.get(&origin_image.0).unwrap();
-    let (columns, rows) = select_piece.get_columns_rows();
-    let width = image.texture_descriptor.size.width;
-    let height = image.texture_descriptor.size.height;
-    let generator = JigsawGenerator::from_rgba8(width, height, &image.data, columns, rows)
-        .expect("Failed to load image");
+    let generator = images
+        .get(&origin_image.0)
+        .ok_or_else(|| "The image isn't loaded yet".to_string())
+        .and_then(|image| {
+            image
+                .clone()
+                .try_into_dynamic()
+                .map_err(|e| format!("Failed to load the image: {e}"))
+        })
+        .and_then(|image| {
+            JigsawGenerator::with_target_pieces(image, select_piece.pieces_count())
+                .map_err(|e| format!("Failed to create the generator: {e}"))
+        });
+    let generator = match generator {
+        Ok(generator) => {
+            // fully transparent parts of cut-out images get no pieces, other images keep the
+            // plain border pieces
+            let image = generator.origin_image();
+            let is_cut_out =
+                image.color().has_alpha() && image.pixels().any(|(_, _, pixel)| pixel.0[3] == 0);
+            let generator = if is_cut_out {
+                generator.alpha_threshold(0)
+            } else {
+                generator
+            };
+            generator.wrap_around(select_game_mode.wrap_around)
+        }
+        Err(message) => {
+            abort_generating(&mut commands, &mut app_state, &play_screen, message);
+            return;
+        }
+    };
+    let (width, height) = generator.origin_image().dimensions();
 
     commands
         .spawn((
@@ -272,6 +327,42 @@ pub struct JigsawPuzzleTemplate(pub JigsawTemplate);
 #[derive(Component)]
 struct CropTask(Task<CommandQueue>);
 
+/// Scale factors of the levels of detail of the piece images, from the lowest to the highest
+const LOD_SCALES: [f32; 3] = [0.5, 1.0, 2.0];
+/// The level of detail the pieces are spawned with
+const BASE_LOD: usize = 1;
+
+/// The piece images of the levels of detail which are shown or being cropped. The other levels
+/// are only cropped once the camera zooms to them, and freed once they aren't shown anymore.
+#[derive(Resource)]
+struct PieceLods {
+    levels: Vec<Option<LodLevel>>,
+    /// The level the sprites of the pieces show
+    current: usize,
+}
+
+impl PieceLods {
+    fn is_loaded(&self, level: usize) -> bool {
+        self.levels[level]
+            .as_ref()
+            .is_some_and(|lod| lod.pages.iter().all(Option::is_some))
+    }
+}
+
+struct LodLevel {
+    /// The atlas page and the index in its layout of every piece
+    pieces: Vec<(usize, usize)>,
+    /// The images and layout of every atlas page, once it is cropped
+    pages: Vec<Option<LodPage>>,
+}
+
+#[derive(Clone)]
+struct LodPage {
+    color: Handle<Image>,
+    white: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+}
+
 #[derive(Component)]
 struct WhiteImage;
 
@@ -281,116 +372,215 @@ struct ColorImage;
 /// Spawn the pieces of the jigsaw puzzle
 fn spawn_piece(
     mut commands: Commands,
-    generator: Res<JigsawPuzzleGenerator>,
+    mut app_state: ResMut<NextState<AppState>>,
+    play_screen: Query<Entity, With<OnPlayScreen>>,
+    generator: Option<Res<JigsawPuzzleGenerator>>,
     select_game_mode: Res<SelectGameMode>,
 ) {
+    // the generator couldn't be set up
+    let Some(generator) = generator else {
+        return;
+    };
     debug!("Start to generate pieces");
-    if let Ok(template) = generator.generate(**select_game_mode, false) {
-        // commands.insert_resource(JigsawPuzzleTemplate(template.clone()));
-        let mut wait_crops = vec![];
-        for piece in template.pieces.iter() {
-            let piece_clone = piece.clone();
-
-            // let calc_position = random_position(&piece, window.resolution.size(), camera.scale);
-            let calc_position = init_position(piece, template.origin_image.dimensions());
-            let entity = commands
-                .spawn((
-                    Piece(piece.clone()),
-                    MoveTogether::default(),
-                    Transform::from_xyz(calc_position.x, calc_position.y, piece.index as f32),
-                    Visibility::Visible,
-                    OnPlayScreen,
-                ))
-                .observe(on_click_piece)
-                .observe(on_move_end)
-                .observe(on_drag_start)
-                .observe(on_drag_end)
-                .observe(on_add_move_start)
-                .observe(on_remove_move_start)
-                .observe(on_selected)
-                .observe(on_not_selected)
-                .id();
-
-            wait_crops.push((entity, piece_clone));
+    let template = match generator.generate(**select_game_mode, false) {
+        Ok(template) => template,
+        Err(e) => {
+            let message = format!("Failed to generate the puzzle: {e}");
+            abort_generating(&mut commands, &mut app_state, &play_screen, message);
+            return;
         }
+    };
+    let mut piece_entities = vec![];
+    for piece in template.pieces.iter() {
+        // let calc_position = random_position(&piece, window.resolution.size(), camera.scale);
+        let calc_position = init_position(piece, template.origin_image.dimensions());
+        let entity = commands
+            .spawn((
+                Piece(piece.clone()),
+                MoveTogether::default(),
+                Transform::from_xyz(calc_position.x, calc_position.y, piece.index as f32),
+                Visibility::Visible,
+                OnPlayScreen,
+            ))
+            .observe(on_click_piece)
+            .observe(on_move_end)
+            .observe(on_drag_start)
+            .observe(on_drag_end)
+            .observe(on_add_move_start)
+            .observe(on_remove_move_start)
+            .observe(on_selected)
+            .observe(on_not_selected)
+            .id();
 
-        if !wait_crops.is_empty() {
-            let thread_pool = AsyncComputeTaskPool::get();
-            for (entity, piece) in wait_crops {
-                let template_clone = template.clone();
-                let task = thread_pool.spawn(async move {
-                    let mut command_queue = CommandQueue::default();
-
-                    debug!("Start to crop piece {}", piece.index);
-                    let cropped_image = piece.crop(&template_clone.origin_image);
-                    let white_image = piece.fill_white(&cropped_image);
-                    command_queue.push(move |mut world: &mut World| {
-                        let mut assets = world.deref_mut().resource_mut::<Assets<Image>>();
-                        let image = assets.add(Image::from_dynamic(
-                            cropped_image,
-                            true,
-                            RenderAssetUsages::RENDER_WORLD,
-                        ));
-                        let white_image = assets.add(Image::from_dynamic(
-                            white_image,
-                            true,
-                            RenderAssetUsages::RENDER_WORLD,
-                        ));
-                        let color_sprite = Sprite {
-                            image,
-                            anchor: Anchor::TopLeft,
-                            custom_size: Some(Vec2::new(
-                                piece.crop_width as f32,
-                                piece.crop_height as f32,
-                            )),
-                            ..default()
-                        };
-
-                        let color_id = world
-                            .spawn((
-                                ColorImage,
-                                color_sprite,
-                                Transform::from_xyz(
-                                    -piece.calc_offset().0,
-                                    piece.calc_offset().1,
-                                    0.0,
-                                ),
-                            ))
-                            .id();
-                        let white_sprite = Sprite {
-                            image: white_image,
-                            anchor: Anchor::TopLeft,
-                            custom_size: Some(Vec2::new(
-                                piece.crop_width as f32,
-                                piece.crop_height as f32,
-                            )),
-                            ..default()
-                        };
-                        let white_id = world
-                            .spawn((
-                                WhiteImage,
-                                white_sprite,
-                                Transform::from_xyz(
-                                    -piece.calc_offset().0,
-                                    piece.calc_offset().1,
-                                    -1.0,
-                                ),
-                            ))
-                            .id();
-
-                        world
-                            .entity_mut(entity)
-                            .add_children(&[color_id, white_id])
-                            .remove::<CropTask>();
-                    });
+        piece_entities.push(entity);
+    }
 
-                    command_queue
-                });
-                commands.entity(entity).insert(CropTask(task));
+    // only the base level is cropped up front, the others when the camera zooms to them
+    let base_level = match crop_lod(&mut commands, &template, BASE_LOD, Some(&piece_entities)) {
+        Ok(level) => level,
+        Err(e) => {
+            // the pieces aren't spawned yet, so the query of the play screen misses them
+            for entity in piece_entities {
+                commands.entity(entity).despawn_recursive();
             }
+            let message = format!("Failed to crop the pieces: {e}");
+            abort_generating(&mut commands, &mut app_state, &play_screen, message);
+            return;
         }
-        commands.send_event(Shuffle::Random);
     };
+    let mut levels: Vec<Option<LodLevel>> = LOD_SCALES.iter().map(|_| None).collect();
+    levels[BASE_LOD] = Some(base_level);
+    commands.insert_resource(JigsawPuzzleTemplate(template));
+    commands.insert_resource(PieceLods {
+        levels,
+        current: BASE_LOD,
+    });
+    commands.send_event(Shuffle::Random);
+}
+
+/// Starts to crop the atlas pages of a level of detail in the background. All pieces are drawn
+/// from a few atlas pages instead of two images per piece. The sprites of the pieces are spawned
+/// once their page is cropped if their entities are given, otherwise the level replaces the
+/// shown one later.
+fn crop_lod(
+    commands: &mut Commands,
+    template: &JigsawTemplate,
+    level: usize,
+    piece_entities: Option<&[Entity]>,
+) -> JigsawResult<LodLevel> {
+    let thread_pool = AsyncComputeTaskPool::get();
+    let atlas_options = AtlasOptions::default()
+        .crop(CropOptions::default().scale(LOD_SCALES[level]))
+        .bevel(Some(BevelOptions::default()));
+    let layout = template.atlas_layout(&atlas_options)?;
+    let mut lod_pieces = vec![(0, 0); template.pieces.len()];
+    for page in 0..layout.page_sizes.len() {
+        let pieces: Vec<(Option<Entity>, JigsawPiece, URect)> = layout
+            .pieces_on_page(page)
+            .enumerate()
+            .map(|(atlas_index, index)| {
+                lod_pieces[index] = (page, atlas_index);
+                let rect = layout.rects[index];
+                let rect = URect::new(rect.x, rect.y, rect.x + rect.width, rect.y + rect.height);
+                let entity = piece_entities.map(|entities| entities[index]);
+                (entity, template.pieces[index].clone(), rect)
+            })
+            .collect();
+        let task_entity = commands.spawn(OnPlayScreen).id();
+        let template_clone = template.clone();
+        let layout_clone = layout.clone();
+        let atlas_options = atlas_options.clone();
+        let task = thread_pool.spawn(async move {
+            let mut command_queue = CommandQueue::default();
+
+            debug!("Start to crop atlas page {page} of level {level}");
+            let atlas_page =
+                match template_clone.render_atlas_page(&layout_clone, page, &atlas_options) {
+                    Ok(atlas_page) => atlas_page,
+                    Err(e) => {
+                        error!("Failed to crop atlas page {page} of level {level}: {e}");
+                        command_queue.push(move |world: &mut World| {
+                            world.despawn(task_entity);
+                        });
+                        return command_queue;
+                    }
+                };
+            let (width, height) = layout_clone.page_sizes[page];
+            command_queue.push(move |mut world: &mut World| {
+                world.despawn(task_entity);
+                // the level may have been freed while it was cropped
+                let is_cropping = world
+                    .get_resource::<PieceLods>()
+                    .is_some_and(|lods| lods.levels[level].is_some());
+                if !is_cropping {
+                    return;
+                }
+                let mut assets = world.deref_mut().resource_mut::<Assets<Image>>();
+                let color_image = assets.add(Image::from_dynamic(
+                    DynamicImage::ImageRgba8(atlas_page.color),
+                    true,
+                    RenderAssetUsages::RENDER_WORLD,
+                ));
+                let white_image = assets.add(Image::from_dynamic(
+                    DynamicImage::ImageRgba8(atlas_page.white),
+                    true,
+                    RenderAssetUsages::RENDER_WORLD,
+                ));
+                let mut atlas_layout = TextureAtlasLayout::new_empty(UVec2::new(width, height));
+                for (_, _, rect) in pieces.iter() {
+                    atlas_layout.add_texture(*rect);
+                }
+                let atlas_layout = world
+                    .resource_mut::<Assets<TextureAtlasLayout>>()
+                    .add(atlas_layout);
+                if let Some(lod) = world.resource_mut::<PieceLods>().levels[level].as_mut() {
+                    lod.pages[page] = Some(LodPage {
+                        color: color_image.clone(),
+                        white: white_image.clone(),
+                        layout: atlas_layout.clone(),
+                    });
+                }
+
+                for (index, (entity, piece, _)) in pieces.into_iter().enumerate() {
+                    let Some(entity) = entity else {
+                        continue;
+                    };
+                    let atlas = TextureAtlas {
+                        layout: atlas_layout.clone(),
+                        index,
+                    };
+                    let color_sprite = Sprite {
+                        image: color_image.clone(),
+                        texture_atlas: Some(atlas.clone()),
+                        anchor: Anchor::TopLeft,
+                        custom_size: Some(Vec2::new(
+                            piece.crop_width as f32,
+                            piece.crop_height as f32,
+                        )),
+                        ..default()
+                    };
+
+                    let color_id = world
+                        .spawn((
+                            ColorImage,
+                            color_sprite,
+                            Transform::from_xyz(-piece.calc_offset().0, piece.calc_offset().1, 0.0),
+                        ))
+                        .id();
+                    let white_sprite = Sprite {
+                        image: white_image.clone(),
+                        texture_atlas: Some(atlas),
+                        anchor: Anchor::TopLeft,
+                        custom_size: Some(Vec2::new(
+                            piece.crop_width as f32,
+                            piece.crop_height as f32,
+                        )),
+                        ..default()
+                    };
+                    let white_id = world
+                        .spawn((
+                            WhiteImage,
+                            white_sprite,
+                            Transform::from_xyz(
+                                -piece.calc_offset().0,
+                                piece.calc_offset().1,
+                                -1.0,
+                            ),
+                        ))
+                        .id();
+
+                    world.entity_mut(entity).add_children(&[color_id, white_id]);
+                }
+            });
+
+            command_queue
+        });
+        commands.entity(task_entity).insert(CropTask(task));
+    }
+    Ok(LodLevel {
+        pieces: lod_pieces,
+        pages: vec![None; layout.page_sizes.len()],
+    })
 }
 
 /// Calculate the position of the piece in the world space
@@ -426,13 +616,18 @@ fn handle_tasks(mut commands: Commands, mut crop_tasks: Query<&mut CropTask>) {
 
 fn count_spawned_piece(
     mut text: Single<&mut Text, With<PieceCount>>,
-    generator: Res<JigsawPuzzleGenerator>,
+    template: Option<Res<JigsawPuzzleTemplate>>,
+    lods: Option<Res<PieceLods>>,
     mut game_state: ResMut<NextState<GameState>>,
     q_pieces: Query<Entity, With<ColorImage>>,
 ) {
+    let (Some(template), Some(lods)) = (template, lods) else {
+        return;
+    };
     let loaded_pieces = q_pieces.iter().count();
-    text.0 = format!("{}/{}", loaded_pieces, generator.pieces_count());
-    if loaded_pieces == generator.pieces_count() {
+    text.0 = format!("{}/{}", loaded_pieces, template.pieces.len());
+    // the other levels of detail are cropped once the camera zooms to them
+    if loaded_pieces == template.pieces.len() && lods.is_loaded(BASE_LOD) {
         game_state.set(GameState::Play);
     }
 }
@@ -537,7 +732,7 @@ pub struct MoveTogether(pub HashSet<Entity>);
 
 fn on_move_end(
     trigger: Trigger<MoveEnd>,
-    generator: Res<JigsawPuzzleGenerator>,
+    template: Res<JigsawPuzzleTemplate>,
     mut query: Query<(Entity, &Piece, &mut Transform, &mut MoveTogether)>,
     mut commands: Commands,
     mut next_state: ResMut<NextState<GameState>>,
@@ -577,37 +772,15 @@ fn on_move_end(
             compare_transform.translation.y,
         );
 
-        let mut has_snapped = false;
-
-        if target.is_on_the_left_side(compare, target_loc, compare_loc) {
-            debug!("{} on the left side {}", target.index, compare.index);
-            target_transform.translation.x = compare_transform.translation.x - target.width;
-            target_transform.translation.y = compare_transform.translation.y;
-            has_snapped = true;
-        }
-
-        if target.is_on_the_right_side(compare, target_loc, compare_loc) {
-            debug!("{} on the right side {}", target.index, compare.index);
-            target_transform.translation.x = compare_transform.translation.x + compare.width;
-            target_transform.translation.y = compare_transform.translation.y;
-            has_snapped = true;
-        }
-
-        if target.is_on_the_top_side(compare, target_loc, compare_loc) {
-            debug!("{} on the top side {}", target.index, compare.index);
-            target_transform.translation.x = compare_transform.translation.x;
-            target_transform.translation.y = compare_transform.translation.y + target.height;
-            has_snapped = true;
-        }
-
-        if target.is_on_the_bottom_side(compare, target_loc, compare_loc) {
-            debug!("{} on the bottom side {}", target.index, compare.index);
-            target_transform.translation.x = compare_transform.translation.x;
-            target_transform.translation.y = compare_transform.translation.y - compare.height;
-            has_snapped = true;
-        }
+        // neighbours may join on any side, e.g. on all six sides of a hexagon piece, and on
+        // opposite borders of a puzzle which wraps around
+        if let Some((offset_x, offset_y)) =
+            template.snap_offset(target.index, compare.index, target_loc, compare_loc)
+        {
+            debug!("{} beside {}", target.index, compare.index);
+            target_transform.translation.x = compare_transform.translation.x + offset_x;
+            target_transform.translation.y = compare_transform.translation.y + offset_y;
 
-        if has_snapped {
             let mut merged_set: HashSet<_> = together1.union(&together2).cloned().collect();
             merged_set.insert(e1);
             merged_set.insert(e2);
@@ -616,7 +789,23 @@ fn on_move_end(
         }
     }
 
-    if all_entities.len() == generator.pieces_count() {
+    // the pieces may form several islands on an image with a transparent background, the
+    // puzzle is finished once every island is merged into one group
+    let mut groups = 0;
+    let mut grouped = HashSet::default();
+    for (entity, _, _, together) in query.iter() {
+        if grouped.contains(&entity) {
+            continue;
+        }
+        groups += 1;
+        grouped.insert(entity);
+        if all_entities.contains(&entity) {
+            grouped.extend(all_entities.iter().copied());
+        } else {
+            grouped.extend(together.iter().copied());
+        }
+    }
+    if groups == template.island_count() {
         debug!("All pieces have been merged");
         next_state.set(GameState::Finish);
     }
@@ -816,8 +1005,12 @@ pub struct BackgroundHintButton;
 fn setup_generating_ui(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    generator: Res<JigsawPuzzleGenerator>,
+    template: Option<Res<JigsawPuzzleTemplate>>,
 ) {
+    // the puzzle couldn't be generated, the main menu shows why
+    let Some(template) = template else {
+        return;
+    };
     commands
         .spawn((
             Node {
@@ -846,7 +1039,7 @@ fn setup_generating_ui(
                 text_font,
             ));
             p.spawn((
-                Text::new(format!("0/{}", generator.pieces_count())),
+                Text::new(format!("0/{}", template.pieces.len())),
                 TextColor(Color::BLACK),
                 PieceCount,
             ));
@@ -1249,6 +1442,72 @@ fn adjust_camera_on_added_sprite(
     camera_2d.scale = target_scale;
 }
 
+/// Shows the level of detail of the piece images which matches the camera scale best, the
+/// lowest one that isn't enlarged on the screen. The level is cropped when it's first needed, the
+/// one shown before is freed once it's replaced.
+fn swap_piece_lod(
+    mut commands: Commands,
+    camera_2d: Single<&OrthographicProjection, (With<Camera2d>, With<IsDefaultUiCamera>)>,
+    template: Res<JigsawPuzzleTemplate>,
+    mut lods: ResMut<PieceLods>,
+    q_pieces: Query<(&Piece, &Children)>,
+    mut q_color: Query<&mut Sprite, (With<ColorImage>, Without<WhiteImage>)>,
+    mut q_white: Query<&mut Sprite, (With<WhiteImage>, Without<ColorImage>)>,
+) {
+    let level = LOD_SCALES
+        .iter()
+        .position(|scale| scale * camera_2d.scale >= 1.0)
+        .unwrap_or(LOD_SCALES.len() - 1);
+    if level == lods.current {
+        return;
+    }
+    if lods.levels[level].is_none() {
+        debug!("Crop the piece images with scale {}", LOD_SCALES[level]);
+        match crop_lod(&mut commands, &template, level, None) {
+            Ok(lod) => lods.levels[level] = Some(lod),
+            Err(e) => error!(
+                "Failed to crop the pieces with scale {}: {e}",
+                LOD_SCALES[level]
+            ),
+        }
+        return;
+    }
+    if !lods.is_loaded(level) {
+        return;
+    }
+    debug!("Show the piece images with scale {}", LOD_SCALES[level]);
+    let Some(lod) = &lods.levels[level] else {
+        return;
+    };
+    for (piece, children) in q_pieces.iter() {
+        let (page, index) = lod.pieces[piece.index];
+        let Some(page) = &lod.pages[page] else {
+            continue;
+        };
+        let atlas = TextureAtlas {
+            layout: page.layout.clone(),
+            index,
+        };
+        for child in children.iter() {
+            if let Ok(mut sprite) = q_color.get_mut(*child) {
+                sprite.image = page.color.clone();
+                sprite.texture_atlas = Some(atlas.clone());
+            }
+            if let Ok(mut sprite) = q_white.get_mut(*child) {
+                sprite.image = page.white.clone();
+                sprite.texture_atlas = Some(atlas.clone());
+            }
+        }
+    }
+    lods.current = level;
+    // dropping the handles frees the images of the levels which aren't shown
+    for (other, lod) in lods.levels.iter_mut().enumerate() {
+        if other != level {
+            *lod = None;
+        }
+    }
+}
+
 #[derive(Event)]
 pub struct AdjustScale(pub f32);
 
@@ -1330,6 +1589,7 @@ fn handle_toggle_puzzle_hint(
     mut event: EventReader<TogglePuzzleHint>,
     selected_query: Query<Entity, With<Selected>>,
     piece_query: Query<(Entity, &Piece, &MoveTogether), Without<Selected>>,
+    template: Res<JigsawPuzzleTemplate>,
     mut commands: Commands,
 ) {
     for _ in event.read() {
@@ -1340,7 +1600,7 @@ fn handle_toggle_puzzle_hint(
         let mut first_entity = None;
         let mut second_entity = None;
         'f1: for (entity, piece, move_together) in piece_query.iter() {
-            if move_together.len() > 0 {
+            if !move_together.is_empty() {
                 continue 'f1;
             }
             first_piece = Some(piece);
@@ -1349,10 +1609,13 @@ fn handle_toggle_puzzle_hint(
         }
         if let Some(first_piece) = first_piece {
             'f2: for (entity, piece, move_together) in piece_query.iter() {
-                if move_together.len() > 0 {
+                if !move_together.is_empty() {
                     continue 'f2;
                 }
-                if first_piece.beside(piece) {
+                if template
+                    .adjacent(first_piece.index)
+                    .any(|index| index == piece.index)
+                {
                     second_entity = Some(entity);
                     break 'f2;
                 }
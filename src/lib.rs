@@ -81,6 +81,10 @@ pub struct OriginImage(pub Handle<Image>);
 #[derive(Debug, Component, Deref, DerefMut, Clone)]
 pub struct Piece(pub JigsawPiece);
 
+/// Why the last puzzle couldn't be generated, shown in the main menu
+#[derive(Debug, Resource, Deref)]
+pub struct GenerateError(pub String);
+
 // Generic system that takes a component as a parameter, and will despawn all entities with that component
 fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
     for entity in &to_despawn {
@@ -119,36 +123,22 @@ enum SelectPiece {
 
 impl core::fmt::Display for SelectPiece {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                SelectPiece::P20 => 20,
-                SelectPiece::P50 => 50,
-                SelectPiece::P100 => 100,
-                SelectPiece::P150 => 150,
-                SelectPiece::P200 => 200,
-                SelectPiece::P250 => 250,
-                SelectPiece::P300 => 300,
-                SelectPiece::P400 => 400,
-                SelectPiece::P500 => 500,
-            }
-        )
+        write!(f, "{}", self.pieces_count())
     }
 }
 
 impl SelectPiece {
-    fn get_columns_rows(&self) -> (usize, usize) {
+    fn pieces_count(&self) -> usize {
         match self {
-            SelectPiece::P20 => (5, 4),
-            SelectPiece::P50 => (10, 5),
-            SelectPiece::P100 => (10, 10),
-            SelectPiece::P150 => (15, 10),
-            SelectPiece::P200 => (20, 10),
-            SelectPiece::P250 => (25, 10),
-            SelectPiece::P300 => (30, 10),
-            SelectPiece::P400 => (20, 20),
-            SelectPiece::P500 => (25, 20),
+            SelectPiece::P20 => 20,
+            SelectPiece::P50 => 50,
+            SelectPiece::P100 => 100,
+            SelectPiece::P150 => 150,
+            SelectPiece::P200 => 200,
+            SelectPiece::P250 => 250,
+            SelectPiece::P300 => 300,
+            SelectPiece::P400 => 400,
+            SelectPiece::P500 => 500,
         }
     }
 
@@ -182,33 +172,54 @@ impl SelectPiece {
 }
 
 #[derive(Debug, Resource, Deref, DerefMut, Default)]
-pub struct SelectGameMode(pub GameMode);
+pub struct SelectGameMode {
+    #[deref]
+    pub game_mode: GameMode,
+    /// Whether the pieces wrap around at the borders of the image, so there are no border pieces
+    pub wrap_around: bool,
+}
 
 impl core::fmt::Display for SelectGameMode {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "{}",
-            match self.0 {
-                GameMode::Classic => "Classic",
-                GameMode::Square => "Square",
+            match (self.game_mode, self.wrap_around) {
+                (GameMode::Classic, false) => "Classic",
+                (GameMode::Classic, true) => "Wrap-around",
+                (GameMode::Square, _) => "Square",
+                (GameMode::Hexagon, _) => "Hexagon",
+                (GameMode::Irregular, _) => "Irregular",
             }
         )
     }
 }
 
 impl SelectGameMode {
+    fn new(game_mode: GameMode, wrap_around: bool) -> Self {
+        SelectGameMode {
+            game_mode,
+            wrap_around,
+        }
+    }
+
     pub fn next(&mut self) {
-        *self = match self.0 {
-            GameMode::Classic => SelectGameMode(GameMode::Square),
-            GameMode::Square => SelectGameMode(GameMode::Classic),
+        *self = match (self.game_mode, self.wrap_around) {
+            (GameMode::Classic, false) => SelectGameMode::new(GameMode::Classic, true),
+            (GameMode::Classic, true) => SelectGameMode::new(GameMode::Square, false),
+            (GameMode::Square, _) => SelectGameMode::new(GameMode::Hexagon, false),
+            (GameMode::Hexagon, _) => SelectGameMode::new(GameMode::Irregular, false),
+            (GameMode::Irregular, _) => SelectGameMode::new(GameMode::Classic, false),
         };
     }
 
     pub fn previous(&mut self) {
-        *self = match self.0 {
-            GameMode::Classic => SelectGameMode(GameMode::Square),
-            GameMode::Square => SelectGameMode(GameMode::Classic),
+        *self = match (self.game_mode, self.wrap_around) {
+            (GameMode::Classic, false) => SelectGameMode::new(GameMode::Irregular, false),
+            (GameMode::Classic, true) => SelectGameMode::new(GameMode::Classic, false),
+            (GameMode::Square, _) => SelectGameMode::new(GameMode::Classic, true),
+            (GameMode::Hexagon, _) => SelectGameMode::new(GameMode::Square, false),
+            (GameMode::Irregular, _) => SelectGameMode::new(GameMode::Hexagon, false),
         };
     }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    despawn_screen, AnimeCamera, AppState, OriginImage, SelectGameMode, SelectPiece,
+    despawn_screen, AnimeCamera, AppState, GenerateError, OriginImage, SelectGameMode, SelectPiece,
     ANIMATION_LAYERS, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON,
 };
 use bevy::animation::{
@@ -16,7 +16,13 @@ pub(crate) fn menu_plugin(app: &mut App) {
         .init_resource::<Dragging>()
         .add_systems(
             OnEnter(AppState::MainMenu),
-            (setup_menu, load_default_images, load_anime).chain(),
+            (
+                setup_menu,
+                show_generate_error.run_if(resource_exists::<GenerateError>),
+                load_default_images,
+                load_anime,
+            )
+                .chain(),
         )
         .add_systems(
             Update,
@@ -58,7 +64,7 @@ struct TextColorProperty;
 impl AnimatableProperty for TextColorProperty {
     type Property = Srgba;
 
-    fn evaluator_id(&self) -> EvaluatorId {
+    fn evaluator_id(&self) -> EvaluatorId<'_> {
         EvaluatorId::Type(TypeId::of::<Self>())
     }
 
@@ -508,6 +514,32 @@ fn setup_menu(
     commands.insert_resource(MenuTimer(Timer::from_seconds(2.9, TimerMode::Once)));
 }
 
+/// Shows why the last puzzle couldn't be generated at the bottom of the menu
+fn show_generate_error(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    generate_error: Res<GenerateError>,
+) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(20.0),
+            left: Val::Px(20.0),
+            ..default()
+        },
+        Text::new(generate_error.as_str()),
+        TextFont {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.8, 0.1, 0.1)),
+        PickingBehavior::IGNORE,
+        OnMenuScreen,
+    ));
+    commands.remove_resource::<GenerateError>();
+}
+
 fn windows_resize_event(mut commands: Commands, mut resize_events: EventReader<WindowResized>) {
     for _ev in resize_events.read() {
         commands.trigger(ShowTitleAnime);